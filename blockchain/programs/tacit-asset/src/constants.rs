@@ -6,8 +6,50 @@ pub const USER_ACCOUNT_SEED: &[u8] = b"user_account";
 /// Seed for access policy account PDA
 pub const ACCESS_POLICY_SEED: &[u8] = b"access_policy";
 
-/// Maximum length for Arweave transaction ID
-pub const MAX_ARWEAVE_ID_LEN: usize = 43;
+/// Seed for program config PDA
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Seed for prepaid credit account PDA
+pub const CREDIT_SEED: &[u8] = b"credit";
+
+/// Seed for memory account PDA
+pub const MEMORY_SEED: &[u8] = b"memory";
+
+/// Seed for memory version account PDA
+pub const MEMORY_VERSION_SEED: &[u8] = b"memory_version";
+
+/// Seed for attestation PDA
+pub const ATTESTATION_SEED: &[u8] = b"attestation";
+
+/// Seed for registered agent PDA
+pub const AGENT_SEED: &[u8] = b"agent";
+
+/// Seed for policy template PDA
+pub const POLICY_TEMPLATE_SEED: &[u8] = b"policy_template";
+
+/// Seed for the PDA linking a policy to a template it applied
+pub const TEMPLATE_BINDING_SEED: &[u8] = b"template_binding";
+
+/// Seed for pending access request PDA
+pub const ACCESS_REQUEST_SEED: &[u8] = b"access_request";
+
+/// Seed for access offer PDA
+pub const ACCESS_OFFER_SEED: &[u8] = b"access_offer";
+
+/// Seed for accepted payment mint PDA
+pub const ACCEPTED_MINT_SEED: &[u8] = b"accepted_mint";
+
+/// Seed for marketplace listing PDA
+pub const LISTING_SEED: &[u8] = b"listing";
+
+/// Length of an Arweave transaction ID (base64url-encoded 32 bytes)
+pub const ARWEAVE_ID_LEN: usize = 43;
+
+/// Maximum length for IPFS content identifier
+pub const MAX_IPFS_CID_LEN: usize = 100;
+
+/// Maximum length for generic storage URI (e.g. Shadow Drive)
+pub const MAX_STORAGE_URI_LEN: usize = 200;
 
 /// Maximum length for metadata URI
 pub const MAX_METADATA_URI_LEN: usize = 200;
@@ -15,8 +57,62 @@ pub const MAX_METADATA_URI_LEN: usize = 200;
 /// Maximum length for batch ID
 pub const MAX_BATCH_ID_LEN: usize = 64;
 
+/// Maximum length for memory type tag
+pub const MAX_MEMORY_TYPE_LEN: usize = 32;
+
+/// Maximum number of asset IDs in a single scoped grant
+pub const MAX_GRANT_SCOPE_ASSETS: usize = 16;
+
 /// Maximum number of access grants per policy
-pub const MAX_ACCESS_GRANTS: usize = 10;
+pub const MAX_ACCESS_GRANTS: usize = 512;
+
+/// Maximum depth of a delegated grant below the owner's grant
+pub const MAX_DELEGATION_DEPTH: u8 = 3;
+
+/// Maximum length for policy template name
+pub const MAX_TEMPLATE_NAME_LEN: usize = 32;
+
+/// Maximum number of grants in a policy template
+pub const MAX_TEMPLATE_GRANTS: usize = 16;
+
+/// Maximum number of grant slots a policy template expands to
+pub const MAX_TEMPLATE_SLOTS: usize = 32;
+
+/// Grant slots allocated when an access policy is created
+pub const INITIAL_GRANT_CAPACITY: usize = 8;
+
+/// Grant slots added each time an access policy runs out of room
+pub const GRANT_CAPACITY_INCREMENT: usize = 8;
+
+/// Maximum number of storage locators (primary plus mirrors) per version
+pub const MAX_STORAGE_LOCATORS: usize = 4;
 
 /// Default memory asset version
 pub const DEFAULT_VERSION: u32 = 1;
+
+/// Maximum length for attestation evidence URI
+pub const MAX_EVIDENCE_URI_LEN: usize = 200;
+
+/// Maximum attestation score
+pub const MAX_ATTESTATION_SCORE: u8 = 100;
+
+/// Maximum age of an agent provenance signature in seconds
+pub const MAX_AGENT_SIGNATURE_AGE: i64 = 300;
+
+/// Bytes per billing unit for storage fees
+pub const BYTES_PER_KIB: u64 = 1024;
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Maximum number of royalty recipients per memory
+pub const MAX_ROYALTY_RECIPIENTS: usize = 5;
+
+/// Maximum combined royalty in basis points
+pub const MAX_ROYALTY_BPS: u16 = 5_000;
+
+/// Maximum number of distinct royalty recipients across an access offer's memories
+pub const MAX_OFFER_ROYALTY_RECIPIENTS: usize = 16;
+
+/// Maximum number of pruned grants a single `prune_grants` call earns the bounty for
+pub const MAX_BOUNTIED_PRUNES: u32 = 16;
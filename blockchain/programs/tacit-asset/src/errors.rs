@@ -49,4 +49,166 @@ pub enum MemoryAssetError {
     
     #[msg("Invalid permissions bitmap")]
     InvalidPermissions,
+    
+    #[msg("Invalid evidence URI length")]
+    InvalidEvidenceUriLength,
+    
+    #[msg("Attestation score out of range")]
+    InvalidAttestationScore,
+    
+    #[msg("Agent signature instruction missing")]
+    MissingAgentSignature,
+    
+    #[msg("Agent signature does not match the agent or content")]
+    InvalidAgentSignature,
+    
+    #[msg("Agent signature timestamp is stale or in the future")]
+    AgentSignatureExpired,
+    
+    #[msg("Invalid Arweave transaction ID encoding")]
+    InvalidArweaveId,
+    
+    #[msg("Invalid IPFS content identifier")]
+    InvalidIpfsCid,
+    
+    #[msg("Invalid storage URI")]
+    InvalidStorageUri,
+    
+    #[msg("Maximum number of storage locators exceeded")]
+    MaxStorageLocatorsExceeded,
+    
+    #[msg("Duplicate storage locator")]
+    DuplicateStorageLocator,
+    
+    #[msg("Content hash does not match the stored version")]
+    ContentHashMismatch,
+    
+    #[msg("Content size must be greater than zero")]
+    InvalidContentSize,
+    
+    #[msg("Storage quota exceeded")]
+    StorageQuotaExceeded,
+    
+    #[msg("Insufficient prepaid credit")]
+    InsufficientCredit,
+    
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+    
+    #[msg("Account uses an outdated layout; run the migration instruction first")]
+    AccountNotMigrated,
+    
+    #[msg("Account is already on the current layout")]
+    AlreadyMigrated,
+    
+    #[msg("Memory is frozen")]
+    MemoryFrozen,
+    
+    #[msg("Memory is not frozen")]
+    MemoryNotFrozen,
+    
+    #[msg("Memory is no longer active")]
+    MemoryNotActive,
+    
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    
+    #[msg("Memory has not reached its expiry")]
+    MemoryNotExpired,
+    
+    #[msg("Content has been redacted")]
+    ContentRedacted,
+    
+    #[msg("Version account does not belong to this memory")]
+    InvalidVersionAccount,
+    
+    #[msg("Maximum access count must be greater than zero")]
+    InvalidMaxAccess,
+    
+    #[msg("Access grant not found")]
+    AccessGrantNotFound,
+    
+    #[msg("Invalid grant scope")]
+    InvalidGrantScope,
+    
+    #[msg("Memory type exceeds maximum length")]
+    InvalidMemoryTypeLength,
+    
+    #[msg("Delegated grant exceeds the delegator's own grant")]
+    InvalidDelegation,
+    
+    #[msg("Maximum delegation depth exceeded")]
+    DelegationDepthExceeded,
+    
+    #[msg("Access grant is not valid yet")]
+    AccessGrantNotYetValid,
+    
+    #[msg("Access grant quota for the current period is used up")]
+    AccessGrantQuotaExceeded,
+    
+    #[msg("Access quota must allow at least one access per non-empty period")]
+    InvalidAccessQuota,
+    
+    #[msg("No expired or exhausted grants to prune")]
+    NothingToPrune,
+    
+    #[msg("Agent account does not belong to this owner")]
+    InvalidAgentAccount,
+    
+    #[msg("Template name is empty or exceeds maximum length")]
+    InvalidTemplateName,
+    
+    #[msg("Policy template has too many grants")]
+    MaxTemplateGrantsExceeded,
+    
+    #[msg("Policy already reflects the latest template version")]
+    TemplateUpToDate,
+    
+    #[msg("Access request is invalid")]
+    InvalidAccessRequest,
+    
+    #[msg("Fee exceeds 100%")]
+    InvalidFeeBps,
+    
+    #[msg("Access offer duration must be positive")]
+    InvalidOfferDuration,
+    
+    #[msg("Payment mint is not accepted")]
+    MintNotAccepted,
+    
+    #[msg("Token account does not match the payment mint or owner")]
+    InvalidTokenAccount,
+    
+    #[msg("Royalty shares are invalid or exceed the maximum")]
+    InvalidRoyalties,
+    
+    #[msg("Royalty recipient account is missing or does not match")]
+    InvalidRoyaltyRecipient,
+    
+    #[msg("Memory is listed for sale")]
+    MemoryListed,
+    
+    #[msg("Listing is reserved for another buyer")]
+    BuyerNotAllowed,
+    
+    #[msg("Listing price exceeds the buyer's maximum")]
+    ListingPriceExceeded,
+    
+    #[msg("Royalty shares are locked after the first sale")]
+    RoyaltiesLocked,
+    
+    #[msg("Grantee already holds a grant for this scope that cannot be replaced")]
+    AccessGrantConflict,
+    
+    #[msg("Access policy is locked down until the owner edits it")]
+    PolicyLockedDown,
+    
+    #[msg("Listing has expired")]
+    ListingExpired,
+    
+    #[msg("Compressed NFT leaf does not belong to the memory")]
+    InvalidLeaf,
+    
+    #[msg("Too many new grants for one instruction; split them across calls")]
+    GrantBatchTooLarge,
 }
@@ -0,0 +1,244 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, Token, TokenAccount};
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+/// Move lamports out of a program-owned account
+pub fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    let from_balance = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(MemoryAssetError::InsufficientCredit)?;
+    let to_balance = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    **from.try_borrow_mut_lamports()? = from_balance;
+    **to.try_borrow_mut_lamports()? = to_balance;
+
+    Ok(())
+}
+
+/// Debit the storage fee for `content_size` bytes from prepaid credit
+/// and pay it to the treasury
+pub fn charge_storage_fee<'info>(
+    config: &ProgramConfig,
+    credit_account: &mut Account<'info, CreditAccount>,
+    treasury: &AccountInfo<'info>,
+    content_size: u64,
+) -> Result<u64> {
+    let fee = config.storage_fee(content_size)?;
+    if fee == 0 {
+        return Ok(0);
+    }
+
+    credit_account.balance = credit_account
+        .balance
+        .checked_sub(fee)
+        .ok_or(MemoryAssetError::InsufficientCredit)?;
+    credit_account.total_spent = credit_account
+        .total_spent
+        .checked_add(fee)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    transfer_lamports(&credit_account.to_account_info(), treasury, fee)?;
+
+    emit!(CreditDebitedEvent {
+        owner: credit_account.owner,
+        amount: fee,
+        content_size,
+        balance: credit_account.balance,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(fee)
+}
+
+/// Check `account` holds `mint` tokens on behalf of `owner`
+pub fn require_token_account(account: &TokenAccount, mint: &Pubkey, owner: &Pubkey) -> Result<()> {
+    require!(
+        account.mint == *mint && account.owner == *owner,
+        MemoryAssetError::InvalidTokenAccount
+    );
+    Ok(())
+}
+
+/// Transfer `amount` tokens from `from`, signed by its owner `authority`
+pub fn transfer_tokens<'info>(
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: authority.clone(),
+            },
+        ),
+        amount,
+    )
+}
+
+/// Charge the storage fee for `content_size` bytes in `accepted_mint`'s
+/// token, paid by `owner` to the mint's treasury account
+pub fn charge_storage_fee_in_tokens<'info>(
+    accepted_mint: &Account<'info, AcceptedMint>,
+    owner_token_account: Option<&Account<'info, TokenAccount>>,
+    treasury_token_account: Option<&Account<'info, TokenAccount>>,
+    token_program: Option<&Program<'info, Token>>,
+    owner: &AccountInfo<'info>,
+    content_size: u64,
+) -> Result<u64> {
+    let (owner_token_account, treasury_token_account, token_program) =
+        match (owner_token_account, treasury_token_account, token_program) {
+            (Some(from), Some(to), Some(token_program)) => (from, to, token_program),
+            _ => return err!(MemoryAssetError::InvalidTokenAccount),
+        };
+    require_token_account(owner_token_account, &accepted_mint.mint, owner.key)?;
+    require!(
+        treasury_token_account.key() == accepted_mint.treasury_token_account,
+        MemoryAssetError::InvalidTokenAccount
+    );
+
+    let fee = accepted_mint.storage_fee(content_size)?;
+    transfer_tokens(token_program, owner_token_account, treasury_token_account, owner, fee)?;
+
+    emit!(TokenFeePaidEvent {
+        owner: *owner.key,
+        mint: accepted_mint.mint,
+        amount: fee,
+        content_size,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(fee)
+}
+
+/// Pay each royalty in `payouts` in lamports from `payer` to the matching
+/// account in `recipients`, returning the total paid
+pub fn pay_royalties<'info>(
+    payouts: &[(Pubkey, u64)],
+    payer: &AccountInfo<'info>,
+    recipients: &[AccountInfo<'info>],
+    system_program: &AccountInfo<'info>,
+) -> Result<u64> {
+    require!(
+        recipients.len() == payouts.len(),
+        MemoryAssetError::InvalidRoyaltyRecipient
+    );
+
+    let mut total: u64 = 0;
+    for ((recipient, amount), to) in payouts.iter().zip(recipients) {
+        require!(
+            to.key() == *recipient,
+            MemoryAssetError::InvalidRoyaltyRecipient
+        );
+        if *amount > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: payer.clone(),
+                        to: to.clone(),
+                    },
+                ),
+                *amount,
+            )?;
+        }
+        total = total
+            .checked_add(*amount)
+            .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+    }
+
+    Ok(total)
+}
+
+/// Pay each royalty in `payouts` in `mint` tokens from `from` to the
+/// recipient's token account in `recipients`, returning the total paid
+pub fn pay_royalties_in_tokens<'info>(
+    payouts: &[(Pubkey, u64)],
+    mint: &Pubkey,
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    authority: &AccountInfo<'info>,
+    recipients: &'info [AccountInfo<'info>],
+) -> Result<u64> {
+    require!(
+        recipients.len() == payouts.len(),
+        MemoryAssetError::InvalidRoyaltyRecipient
+    );
+
+    let mut total: u64 = 0;
+    for ((recipient, amount), info) in payouts.iter().zip(recipients) {
+        let to = Account::<TokenAccount>::try_from(info)?;
+        require!(
+            to.mint == *mint && to.owner == *recipient,
+            MemoryAssetError::InvalidRoyaltyRecipient
+        );
+        transfer_tokens(token_program, from, &to, authority, *amount)?;
+        total = total
+            .checked_add(*amount)
+            .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+    }
+
+    Ok(total)
+}
+
+/// Pay `cranker` the configured bounty for `grants_bountied` pruned grants,
+/// at most `MAX_BOUNTIED_PRUNES` of them, from prepaid credit, capped at the
+/// available balance
+pub fn pay_prune_bounty<'info>(
+    config: &ProgramConfig,
+    credit_account: &mut Account<'info, CreditAccount>,
+    cranker: &AccountInfo<'info>,
+    grants_bountied: u32,
+) -> Result<u64> {
+    let bounty = config
+        .prune_bounty
+        .checked_mul(grants_bountied.min(MAX_BOUNTIED_PRUNES) as u64)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?
+        .min(credit_account.balance);
+    if bounty == 0 {
+        return Ok(0);
+    }
+
+    credit_account.balance -= bounty;
+    credit_account.total_spent = credit_account
+        .total_spent
+        .checked_add(bounty)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    transfer_lamports(&credit_account.to_account_info(), cranker, bounty)?;
+
+    Ok(bounty)
+}
+
+#[event]
+pub struct CreditDebitedEvent {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub content_size: u64,
+    pub balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenFeePaidEvent {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub content_size: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct AddAcceptedMint<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ MemoryAssetError::Unauthorized
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = AcceptedMint::LEN,
+        seeds = [ACCEPTED_MINT_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+
+    pub mint: Account<'info, Mint>,
+
+    /// Treasury's account for `mint`, receiving fees paid in it
+    #[account(
+        constraint = treasury_token_account.mint == mint.key()
+            @ MemoryAssetError::InvalidTokenAccount,
+        constraint = treasury_token_account.owner == config.treasury
+            @ MemoryAssetError::InvalidTokenAccount
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<AddAcceptedMint>,
+    base_storage_fee: u64,
+    fee_per_kib: u64,
+) -> Result<()> {
+    let accepted_mint = &mut ctx.accounts.accepted_mint;
+
+    accepted_mint.schema_version = AcceptedMint::SCHEMA_VERSION;
+    accepted_mint.mint = ctx.accounts.mint.key();
+    accepted_mint.treasury_token_account = ctx.accounts.treasury_token_account.key();
+    accepted_mint.base_storage_fee = base_storage_fee;
+    accepted_mint.fee_per_kib = fee_per_kib;
+    accepted_mint.created_at = Clock::get()?.unix_timestamp;
+    accepted_mint.bump = ctx.bumps.accepted_mint;
+
+    msg!("Payment mint accepted");
+    msg!("Mint: {}", accepted_mint.mint);
+    msg!("Treasury token account: {}", accepted_mint.treasury_token_account);
+    msg!("Base storage fee: {}", base_storage_fee);
+    msg!("Fee per KiB: {}", fee_per_kib);
+
+    Ok(())
+}
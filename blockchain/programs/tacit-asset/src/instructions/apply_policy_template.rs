@@ -0,0 +1,207 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
+use super::update_access_policy::reserve_grant_slots;
+
+#[derive(Accounts)]
+#[instruction(asset_id: Option<Pubkey>)]
+pub struct ApplyPolicyTemplate<'info> {
+    pub template: Account<'info, PolicyTemplate>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = TemplateBinding::LEN,
+        seeds = [
+            TEMPLATE_BINDING_SEED,
+            owner.key().as_ref(),
+            template.key().as_ref(),
+            asset_id.unwrap_or_default().as_ref()
+        ],
+        bump
+    )]
+    pub binding: Account<'info, TemplateBinding>,
+
+    #[account(
+        mut,
+        seeds = [ACCESS_POLICY_SEED, owner.key().as_ref()],
+        bump = AccessPolicyAccount::current_bump(&access_policy)?,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    #[account(
+        mut,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    /// Memory the template is narrowed to, required with `asset_id`
+    #[account(has_one = owner @ MemoryAssetError::InvalidOwner)]
+    pub memory_account: Option<Account<'info, MemoryAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<ApplyPolicyTemplate>,
+    asset_id: Option<Pubkey>,
+    propagate: bool,
+    sync_default_policy: bool,
+) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    let clock = Clock::get()?;
+
+    if let Some(asset_id) = asset_id {
+        let memory_account = ctx
+            .accounts
+            .memory_account
+            .as_ref()
+            .ok_or(MemoryAssetError::InvalidGrantScope)?;
+        require!(
+            memory_account.asset_id == asset_id,
+            MemoryAssetError::InvalidGrantScope
+        );
+    }
+
+    let binding = &mut ctx.accounts.binding;
+    binding.schema_version = TemplateBinding::SCHEMA_VERSION;
+    binding.owner = owner;
+    binding.template = ctx.accounts.template.key();
+    binding.asset_id = asset_id;
+    binding.propagate = propagate;
+    binding.sync_default_policy = sync_default_policy;
+    binding.applied_keys = Vec::new();
+    binding.bump = ctx.bumps.binding;
+
+    let grants_applied = apply_template(
+        &ctx.accounts.access_policy.to_account_info(),
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        binding,
+        &ctx.accounts.template,
+        true,
+        clock.unix_timestamp,
+    )?;
+
+    // Applying is an owner edit, so it lifts a lockdown
+    ctx.accounts.access_policy.load_mut()?.locked_down = 0;
+
+    // Increment policy version
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.access_policy_version = user_account
+        .access_policy_version
+        .checked_add(1)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    msg!("Policy template applied");
+    msg!("Owner: {}", owner);
+    msg!("Template: {}", binding.template);
+    msg!("Asset ID: {:?}", asset_id);
+    msg!("Grants applied: {}", grants_applied);
+    msg!("Propagate: {}", propagate);
+    msg!("Sync default policy: {}", sync_default_policy);
+    msg!("Policy version: {}", user_account.access_policy_version);
+
+    Ok(())
+}
+
+/// Write `template`'s grants into the policy in `policy_info`, replacing the
+/// slots written by the binding's previous application, and its default
+/// policy when `set_default_policy` is set. Slots the binding did not write,
+/// or that were rewritten since, are left alone and the template grant for
+/// them is skipped. Returns the number of slots written.
+pub fn apply_template<'info>(
+    policy_info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    binding: &mut TemplateBinding,
+    template: &PolicyTemplate,
+    set_default_policy: bool,
+    current_time: i64,
+) -> Result<usize> {
+    let mut new_slots = Vec::new();
+    for grant in &template.grants {
+        // Template expiries are absolute, so they lapse between syncs; an
+        // expired grant is left out (and its old slot dropped) rather than
+        // failing every later application
+        if grant.expires_at.map_or(false, |expires_at| expires_at <= current_time) {
+            continue;
+        }
+        let mut grant = grant.clone();
+        if let Some(asset_id) = binding.asset_id {
+            grant.scope = GrantScope::Assets(vec![asset_id]);
+        }
+        grant.validate(&binding.owner, current_time)?;
+        new_slots.extend(grant.to_slots());
+    }
+    for slot in new_slots.iter_mut() {
+        slot.origin = GrantSlot::ORIGIN_TEMPLATE;
+    }
+
+    // A slot is the binding's while it holds what the binding last wrote;
+    // an owner rewrite, purchase or delegation under the same key is not
+    let written_here = |slot: &GrantSlot| {
+        slot.origin == GrantSlot::ORIGIN_TEMPLATE
+            && slot.depth == 0
+            && binding.applied_keys.contains(&GrantKey::from(slot))
+    };
+
+    // Drop the binding's slots the template no longer writes, and skip
+    // template grants whose slot belongs to someone else
+    let skipped = {
+        let mut data = policy_info.try_borrow_mut_data()?;
+        let (access_policy, slots) = AccessPolicyAccount::unpack_mut(&mut data);
+        access_policy.remove_grants(slots, |slot| {
+            written_here(slot)
+                && !new_slots.iter().any(|new_slot| new_slot.key() == slot.key())
+        });
+
+        let count = new_slots.len();
+        new_slots.retain(|new_slot| {
+            access_policy
+                .find_grant(slots, new_slot)
+                .map_or(true, |index| written_here(&slots[index]))
+        });
+        count - new_slots.len()
+    };
+    if skipped > 0 {
+        msg!("Grants skipped: {}", skipped);
+    }
+
+    reserve_grant_slots(policy_info, payer, system_program, &new_slots)?;
+
+    let mut data = policy_info.try_borrow_mut_data()?;
+    let (access_policy, slots) = AccessPolicyAccount::unpack_mut(&mut data);
+    access_policy.capacity = slots.len() as u32;
+    let mut applied_keys: Vec<GrantKey> = Vec::with_capacity(new_slots.len());
+    for slot in new_slots {
+        let key = GrantKey::from(&slot);
+        if !applied_keys.contains(&key) {
+            applied_keys.push(key);
+        }
+        access_policy.upsert_grant(slots, slot)?;
+    }
+    access_policy.prune_orphans(slots);
+
+    // Asset-scoped applications leave the rest of the policy alone
+    if set_default_policy && binding.asset_id.is_none() {
+        access_policy.default_policy = template.default_policy as u8;
+    }
+    access_policy.updated_at = current_time;
+
+    let written = applied_keys.len();
+    binding.applied_keys = applied_keys;
+    binding.applied_version = template.version;
+    binding.applied_at = current_time;
+
+    Ok(written)
+}
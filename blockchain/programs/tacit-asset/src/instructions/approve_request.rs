@@ -0,0 +1,150 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
+use crate::permissions::Permissions;
+use super::update_access_policy::{require_policy_admin, reserve_grant_slots};
+
+#[derive(Accounts)]
+pub struct ApproveRequest<'info> {
+    #[account(
+        mut,
+        close = requester,
+        seeds = [ACCESS_REQUEST_SEED, owner.key().as_ref(), requester.key().as_ref()],
+        bump = access_request.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        has_one = requester @ MemoryAssetError::InvalidAccessRequest
+    )]
+    pub access_request: Account<'info, AccessRequest>,
+
+    #[account(
+        mut,
+        seeds = [ACCESS_POLICY_SEED, owner.key().as_ref()],
+        bump = AccessPolicyAccount::current_bump(&access_policy)?,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    #[account(
+        mut,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    /// CHECK: Policy owner, validated by `has_one`
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: Requester receiving the request rent, validated by `has_one`
+    #[account(mut)]
+    pub requester: UncheckedAccount<'info>,
+
+    /// Policy owner or a grantee holding `Permissions::ADMIN`; pays rent when
+    /// the policy needs more grant slots
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<ApproveRequest>,
+    expires_at: Option<i64>,
+    max_access: Option<u32>,
+) -> Result<()> {
+    let policy_info = ctx.accounts.access_policy.to_account_info();
+    let access_request = &ctx.accounts.access_request;
+    let owner = ctx.accounts.owner.key();
+    let authority = ctx.accounts.authority.key();
+    let clock = Clock::get()?;
+
+    require_policy_admin(&policy_info, &authority, clock.unix_timestamp)?;
+    // Pending requests wait until the owner edits the locked-down policy
+    require!(
+        !ctx.accounts.access_policy.load()?.is_locked_down(),
+        MemoryAssetError::PolicyLockedDown
+    );
+
+    // The approver picks the terms; scope and permissions are as requested
+    let grant = AccessGrant {
+        grantee: access_request.requester,
+        permissions: access_request.permissions,
+        expires_at,
+        max_access,
+        current_access: 0,
+        scope: access_request.scope.clone(),
+        effect: GrantEffect::Allow,
+        not_before: None,
+        quota: None,
+    };
+    grant.validate(&owner, clock.unix_timestamp)?;
+    let new_slots = grant.to_slots();
+
+    reserve_grant_slots(
+        &policy_info,
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &new_slots,
+    )?;
+
+    let orphans_removed = {
+        let mut data = policy_info.try_borrow_mut_data()?;
+        let (access_policy, slots) = AccessPolicyAccount::unpack_mut(&mut data);
+        access_policy.capacity = slots.len() as u32;
+
+        // Approving a request never lifts a deny or downgrades an admin
+        for slot in &new_slots {
+            if let Ok(index) = access_policy.find_grant(slots, slot) {
+                let existing = &slots[index];
+                require!(
+                    !existing.is_deny() && !existing.permissions().contains(Permissions::ADMIN),
+                    MemoryAssetError::AccessGrantConflict
+                );
+            }
+        }
+        for slot in new_slots {
+            access_policy.upsert_grant(slots, slot)?;
+        }
+        // Delegations hang off grants that may just have lost `SHARE`
+        let orphans_removed = access_policy.prune_orphans(slots);
+        access_policy.updated_at = clock.unix_timestamp;
+        orphans_removed
+    };
+
+    // Increment policy version
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.access_policy_version = user_account
+        .access_policy_version
+        .checked_add(1)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    msg!("Access request approved");
+    msg!("Owner: {}", owner);
+    msg!("Requester: {}", grant.grantee);
+    msg!("Approved by: {}", authority);
+    msg!("Expires at: {:?}", expires_at);
+    msg!("Delegations removed: {}", orphans_removed);
+    msg!("Policy version: {}", user_account.access_policy_version);
+
+    emit!(AccessRequestResolvedEvent {
+        owner,
+        requester: grant.grantee,
+        approved: true,
+        resolved_by: authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AccessRequestResolvedEvent {
+    pub owner: Pubkey,
+    pub requester: Pubkey,
+    pub approved: bool,
+    pub resolved_by: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+#[instruction(asset_id: Pubkey)]
+pub struct AttestMemory<'info> {
+    #[account(
+        init,
+        payer = attester,
+        space = Attestation::LEN,
+        seeds = [ATTESTATION_SEED, asset_id.as_ref(), attester.key().as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// Memory being attested to
+    #[account(
+        seeds = [MEMORY_SEED, asset_id.as_ref()],
+        bump = memory_account.bump
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    #[account(mut)]
+    pub attester: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<AttestMemory>,
+    asset_id: Pubkey,
+    claim_type: ClaimType,
+    score: u8,
+    evidence_uri: Option<String>,
+) -> Result<()> {
+    let attestation = &mut ctx.accounts.attestation;
+    let clock = Clock::get()?;
+
+    // Burned memories no longer exist to be vouched for
+    require!(
+        ctx.accounts.memory_account.status != MemoryStatus::Burned,
+        MemoryAssetError::MemoryNotActive
+    );
+
+    // Validate inputs
+    require!(
+        score <= MAX_ATTESTATION_SCORE,
+        MemoryAssetError::InvalidAttestationScore
+    );
+    if let Some(uri) = &evidence_uri {
+        require!(
+            uri.len() <= MAX_EVIDENCE_URI_LEN,
+            MemoryAssetError::InvalidEvidenceUriLength
+        );
+    }
+
+    attestation.schema_version = Attestation::SCHEMA_VERSION;
+    attestation.asset_id = asset_id;
+    attestation.attester = ctx.accounts.attester.key();
+    attestation.claim_type = claim_type;
+    attestation.score = score;
+    attestation.evidence_uri = evidence_uri.clone();
+    attestation.created_at = clock.unix_timestamp;
+    attestation.bump = ctx.bumps.attestation;
+
+    msg!("Memory attested");
+    msg!("Asset ID: {}", asset_id);
+    msg!("Attester: {}", attestation.attester);
+    msg!("Claim type: {:?}", claim_type);
+    msg!("Score: {}", score);
+
+    emit!(AttestationCreatedEvent {
+        asset_id,
+        attester: attestation.attester,
+        claim_type,
+        score,
+        evidence_uri,
+        timestamp: attestation.created_at,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AttestationCreatedEvent {
+    pub asset_id: Pubkey,
+    pub attester: Pubkey,
+    pub claim_type: ClaimType,
+    pub score: u8,
+    pub evidence_uri: Option<String>,
+    pub timestamp: i64,
+}
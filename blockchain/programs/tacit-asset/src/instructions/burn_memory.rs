@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::program::Bubblegum;
+use spl_account_compression::{program::SplAccountCompression, Noop};
+use crate::state::*;
+use crate::constants::*;
+use crate::permissions::Permissions;
+use super::update_access_policy::check_access;
+
+#[derive(Accounts)]
+pub struct BurnMemory<'info> {
+    #[account(
+        mut,
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    #[account(
+        seeds = [ACCESS_POLICY_SEED, memory_account.owner.as_ref()],
+        bump = AccessPolicyAccount::current_bump(&access_policy)?
+    )]
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    /// Memory owner or a grantee holding `Permissions::DELETE`
+    pub authority: Signer<'info>,
+
+    /// CHECK: Merkle tree account
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Tree authority PDA
+    pub tree_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Log wrapper
+    pub log_wrapper: Program<'info, Noop>,
+
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<BurnMemory>) -> Result<()> {
+    let memory_account = &mut ctx.accounts.memory_account;
+    let authority = ctx.accounts.authority.key();
+    let clock = Clock::get()?;
+
+    {
+        let policy_info = ctx.accounts.access_policy.to_account_info();
+        let data = policy_info.try_borrow_data()?;
+        let (access_policy, slots) = AccessPolicyAccount::unpack(&data);
+        check_access(access_policy, slots, memory_account, &authority, Permissions::DELETE)?;
+    }
+
+    // Frozen memories are under a hold and cannot be burned
+    memory_account.require_mutable()?;
+
+    memory_account.status = MemoryStatus::Burned;
+    memory_account.updated_at = clock.unix_timestamp;
+
+    msg!("Memory burned");
+    msg!("Owner: {}", memory_account.owner);
+    msg!("Asset ID: {}", memory_account.asset_id);
+    msg!("Burned by: {}", authority);
+
+    // Note: Actual Bubblegum burn CPI call would be here
+    // In production, you would call:
+    // mpl_bubblegum::cpi::burn(...)
+
+    emit!(MemoryBurnedEvent {
+        owner: memory_account.owner,
+        asset_id: memory_account.asset_id,
+        authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MemoryBurnedEvent {
+    pub owner: Pubkey,
+    pub asset_id: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,193 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use mpl_bubblegum::program::Bubblegum;
+use spl_account_compression::{program::SplAccountCompression, Noop};
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::fees::pay_royalties;
+
+#[derive(Accounts)]
+pub struct BuyMemory<'info> {
+    #[account(
+        mut,
+        seeds = [LISTING_SEED, memory_account.asset_id.as_ref()],
+        bump = listing.bump,
+        has_one = seller @ MemoryAssetError::InvalidOwner,
+        close = seller
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(
+        mut,
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump,
+        constraint = memory_account.owner == seller.key() @ MemoryAssetError::InvalidOwner
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// CHECK: Protocol fee recipient, validated against the config
+    #[account(mut, address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Seller receiving the proceeds and listing rent, validated by `has_one`
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    /// Buyer; pays the price and becomes the owner
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Merkle tree account
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Tree authority PDA
+    pub tree_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Log wrapper
+    pub log_wrapper: Program<'info, Noop>,
+
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: writable recipient of each royalty share, in order,
+    // then the proof path of the compressed NFT leaf
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BuyMemory<'info>>,
+    max_price: u64,
+    leaf: CompressedLeaf,
+) -> Result<()> {
+    let listing = &ctx.accounts.listing;
+    let seller = listing.seller;
+    let buyer = ctx.accounts.buyer.key();
+    let price = listing.price;
+    let clock = Clock::get()?;
+
+    require!(buyer != seller, MemoryAssetError::InvalidOwner);
+    require!(listing.allows(&buyer), MemoryAssetError::BuyerNotAllowed);
+    require!(
+        clock.unix_timestamp < listing.expires_at,
+        MemoryAssetError::ListingExpired
+    );
+    // Guards against the seller relisting at a higher price in between
+    require!(price <= max_price, MemoryAssetError::ListingPriceExceeded);
+
+    // A memory frozen or expired while listed cannot be sold
+    ctx.accounts.memory_account.require_active()?;
+    leaf.require_asset(
+        &ctx.accounts.merkle_tree.key(),
+        &ctx.accounts.memory_account.asset_id,
+    )?;
+
+    let protocol_fee = ctx.accounts.config.protocol_fee(price)?;
+    let payouts = ctx
+        .accounts
+        .memory_account
+        .royalty_payouts(price - protocol_fee)?;
+
+    require!(
+        ctx.remaining_accounts.len() >= payouts.len(),
+        MemoryAssetError::InvalidRoyaltyRecipient
+    );
+    let (recipients, proof) = ctx.remaining_accounts.split_at(payouts.len());
+
+    let buyer_info = ctx.accounts.buyer.to_account_info();
+    let royalties = pay_royalties(
+        &payouts,
+        &buyer_info,
+        recipients,
+        &ctx.accounts.system_program.to_account_info(),
+    )?;
+    let proceeds = (price - protocol_fee)
+        .checked_sub(royalties)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    for (to, amount) in [
+        (ctx.accounts.treasury.to_account_info(), protocol_fee),
+        (ctx.accounts.seller.to_account_info(), proceeds),
+    ] {
+        if amount > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: buyer_info.clone(),
+                        to,
+                    },
+                ),
+                amount,
+            )?;
+        }
+    }
+
+    // The listing PDA signs as leaf delegate to hand the compressed NFT to
+    // the buyer, in the same transaction as the payment
+    let asset_id = ctx.accounts.memory_account.asset_id;
+    let bump = [listing.bump];
+    let listing_seeds: &[&[u8]] = &[LISTING_SEED, asset_id.as_ref(), &bump];
+    mpl_bubblegum::cpi::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.bubblegum_program.to_account_info(),
+            mpl_bubblegum::cpi::accounts::Transfer {
+                tree_authority: ctx.accounts.tree_authority.to_account_info(),
+                leaf_owner: ctx.accounts.seller.to_account_info(),
+                leaf_delegate: listing.to_account_info(),
+                new_leaf_owner: buyer_info.clone(),
+                merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+                compression_program: ctx.accounts.compression_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            &[listing_seeds],
+        )
+        .with_remaining_accounts(proof.to_vec()),
+        leaf.root,
+        leaf.data_hash,
+        leaf.creator_hash,
+        leaf.nonce,
+        leaf.index,
+    )?;
+
+    let memory_account = &mut ctx.accounts.memory_account;
+    memory_account.owner = buyer;
+    memory_account.delegate = None;
+    memory_account.royalties_locked = true;
+    memory_account.updated_at = clock.unix_timestamp;
+
+    msg!("Memory sold");
+    msg!("Asset ID: {}", memory_account.asset_id);
+    msg!("Seller: {}", seller);
+    msg!("Buyer: {}", buyer);
+    msg!("Price: {}", price);
+    msg!("Protocol fee: {}", protocol_fee);
+    msg!("Royalties: {}", royalties);
+
+    emit!(MemorySoldEvent {
+        asset_id: memory_account.asset_id,
+        seller,
+        buyer,
+        price,
+        protocol_fee,
+        royalties,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MemorySoldEvent {
+    pub asset_id: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub price: u64,
+    pub protocol_fee: u64,
+    pub royalties: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::program::Bubblegum;
+use spl_account_compression::{program::SplAccountCompression, Noop};
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    #[account(
+        mut,
+        seeds = [LISTING_SEED, memory_account.asset_id.as_ref()],
+        bump = listing.bump,
+        has_one = seller @ MemoryAssetError::InvalidOwner,
+        close = seller
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(
+        mut,
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    /// Seller; receives the listing rent
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// CHECK: Merkle tree account
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Tree authority PDA
+    pub tree_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Log wrapper
+    pub log_wrapper: Program<'info, Noop>,
+
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: proof path of the compressed NFT leaf
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CancelListing<'info>>,
+    leaf: CompressedLeaf,
+) -> Result<()> {
+    let memory_account = &mut ctx.accounts.memory_account;
+    let listing = &ctx.accounts.listing;
+    let clock = Clock::get()?;
+
+    leaf.require_asset(&ctx.accounts.merkle_tree.key(), &memory_account.asset_id)?;
+
+    // Cancelling is allowed even if the memory was frozen or expired
+    // while listed, so the seller can always take it off the market
+    memory_account.delegate = None;
+    memory_account.updated_at = clock.unix_timestamp;
+
+    msg!("Listing cancelled");
+    msg!("Asset ID: {}", memory_account.asset_id);
+    msg!("Seller: {}", listing.seller);
+
+    // Hand the leaf delegate back from the listing PDA to the seller
+    mpl_bubblegum::cpi::delegate(
+        CpiContext::new(
+            ctx.accounts.bubblegum_program.to_account_info(),
+            mpl_bubblegum::cpi::accounts::Delegate {
+                tree_authority: ctx.accounts.tree_authority.to_account_info(),
+                leaf_owner: ctx.accounts.seller.to_account_info(),
+                previous_leaf_delegate: listing.to_account_info(),
+                new_leaf_delegate: ctx.accounts.seller.to_account_info(),
+                merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+                compression_program: ctx.accounts.compression_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        leaf.root,
+        leaf.data_hash,
+        leaf.creator_hash,
+        leaf.nonce,
+        leaf.index,
+    )?;
+
+    emit!(ListingCancelledEvent {
+        asset_id: memory_account.asset_id,
+        listing: listing.key(),
+        seller: listing.seller,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ListingCancelledEvent {
+    pub asset_id: Pubkey,
+    pub listing: Pubkey,
+    pub seller: Pubkey,
+    pub timestamp: i64,
+}
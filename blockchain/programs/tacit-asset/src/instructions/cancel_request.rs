@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use super::approve_request::AccessRequestResolvedEvent;
+
+#[derive(Accounts)]
+pub struct CancelRequest<'info> {
+    #[account(
+        mut,
+        close = requester,
+        seeds = [ACCESS_REQUEST_SEED, owner.key().as_ref(), requester.key().as_ref()],
+        bump = access_request.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        has_one = requester @ MemoryAssetError::InvalidAccessRequest
+    )]
+    pub access_request: Account<'info, AccessRequest>,
+
+    /// CHECK: Owner that was asked, validated by `has_one`
+    pub owner: UncheckedAccount<'info>,
+
+    /// Requester withdrawing the request; receives the rent back
+    #[account(mut)]
+    pub requester: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<CancelRequest>) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    let requester = ctx.accounts.requester.key();
+    let clock = Clock::get()?;
+
+    msg!("Access request cancelled");
+    msg!("Owner: {}", owner);
+    msg!("Requester: {}", requester);
+
+    emit!(AccessRequestResolvedEvent {
+        owner,
+        requester,
+        approved: false,
+        resolved_by: requester,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct CloseAccessOffer<'info> {
+    /// Grants already sold stay valid until they expire
+    #[account(
+        mut,
+        close = owner,
+        seeds = [ACCESS_OFFER_SEED, owner.key().as_ref(), &access_offer.offer_id.to_le_bytes()],
+        bump = access_offer.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub access_offer: Account<'info, AccessOffer>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<CloseAccessOffer>) -> Result<()> {
+    msg!("Access offer closed");
+    msg!("Owner: {}", ctx.accounts.owner.key());
+    msg!("Offer ID: {}", ctx.accounts.access_offer.offer_id);
+    msg!("Sales: {}", ctx.accounts.access_offer.sales);
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
+
+#[derive(Accounts)]
+pub struct CloseUser<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [ACCESS_POLICY_SEED, owner.key().as_ref()],
+        bump = AccessPolicyAccount::current_bump(&access_policy)?,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    /// Remaining credit is refunded to the owner together with rent
+    #[account(
+        mut,
+        close = owner,
+        seeds = [CREDIT_SEED, owner.key().as_ref()],
+        bump = credit_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub credit_account: Account<'info, CreditAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<CloseUser>) -> Result<()> {
+    let credit_account = &ctx.accounts.credit_account;
+
+    msg!("User account closed");
+    msg!("Owner: {}", ctx.accounts.owner.key());
+    msg!("Credit refunded: {}", credit_account.balance);
+
+    emit!(UserClosedEvent {
+        owner: ctx.accounts.owner.key(),
+        refunded_credit: credit_account.balance,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct UserClosedEvent {
+    pub owner: Pubkey,
+    pub refunded_credit: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
+use crate::permissions::Permissions;
+
+#[derive(Accounts)]
+#[instruction(offer_id: u64)]
+pub struct CreateAccessOffer<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = AccessOffer::LEN,
+        seeds = [ACCESS_OFFER_SEED, owner.key().as_ref(), &offer_id.to_le_bytes()],
+        bump
+    )]
+    pub access_offer: Account<'info, AccessOffer>,
+
+    #[account(
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    /// Mint the offer is priced in, required with `payment_mint`
+    #[account(
+        seeds = [ACCEPTED_MINT_SEED, accepted_mint.mint.as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Option<Account<'info, AcceptedMint>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: the writable MemoryAccount of each memory the offer
+    // covers, see `offer_royalties`
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CreateAccessOffer<'info>>,
+    offer_id: u64,
+    scope: GrantScope,
+    permissions: u8,
+    duration: i64,
+    price: u64,
+    payment_mint: Option<Pubkey>,
+    max_access: Option<u32>,
+) -> Result<()> {
+    let access_offer = &mut ctx.accounts.access_offer;
+    let clock = Clock::get()?;
+
+    // Policy administration is not for sale
+    let offered = Permissions::validate(permissions)?;
+    require!(
+        !offered.is_empty() && !offered.contains(Permissions::ADMIN),
+        MemoryAssetError::InvalidPermissions
+    );
+    require!(duration > 0, MemoryAssetError::InvalidOfferDuration);
+    require!(price > 0, MemoryAssetError::InvalidAmount);
+    require!(max_access != Some(0), MemoryAssetError::InvalidMaxAccess);
+    scope.validate()?;
+    if let Some(payment_mint) = payment_mint {
+        require!(
+            ctx.accounts
+                .accepted_mint
+                .as_ref()
+                .map_or(false, |accepted_mint| accepted_mint.mint == payment_mint),
+            MemoryAssetError::MintNotAccepted
+        );
+    }
+
+    // Royalties are fixed now, so buyers cannot pick which memories pay
+    let royalties = offer_royalties(
+        ctx.program_id,
+        &ctx.accounts.owner.key(),
+        &scope,
+        ctx.remaining_accounts,
+    )?;
+
+    access_offer.schema_version = AccessOffer::SCHEMA_VERSION;
+    access_offer.owner = ctx.accounts.owner.key();
+    access_offer.offer_id = offer_id;
+    access_offer.scope = scope;
+    access_offer.permissions = permissions;
+    access_offer.duration = duration;
+    access_offer.price = price;
+    access_offer.payment_mint = payment_mint;
+    access_offer.max_access = max_access;
+    access_offer.royalties = royalties;
+    access_offer.sales = 0;
+    access_offer.created_at = clock.unix_timestamp;
+    access_offer.bump = ctx.bumps.access_offer;
+
+    msg!("Access offer created");
+    msg!("Owner: {}", access_offer.owner);
+    msg!("Offer ID: {}", offer_id);
+    msg!("Permissions: {:?}", offered);
+    msg!("Duration: {}s", duration);
+    msg!("Price: {}", price);
+    msg!("Payment mint: {:?}", payment_mint);
+    msg!("Royalty recipients: {}", access_offer.royalties.len());
+
+    Ok(())
+}
+
+/// Creator royalties on the offer's proceeds, combined across the covered
+/// memories leading `memory_infos`. Asset offers list every scoped asset in
+/// scope order; batch, type and `All` offers list the owner's covered
+/// memories in ascending asset ID order. Proceeds split evenly across the
+/// memories, so a recipient's combined rate is the sum of its rates over the
+/// number of memories, rounded down. Each memory's royalty shares lock here.
+fn offer_royalties<'info>(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    scope: &GrantScope,
+    memory_infos: &'info [AccountInfo<'info>],
+) -> Result<Vec<RoyaltyShare>> {
+    if let GrantScope::Assets(asset_ids) = scope {
+        require!(
+            memory_infos.len() == asset_ids.len(),
+            MemoryAssetError::MemoryAssetNotFound
+        );
+    }
+
+    let mut combined: Vec<(Pubkey, u64)> = Vec::new();
+    let mut previous: Option<Pubkey> = None;
+    for (i, info) in memory_infos.iter().enumerate() {
+        require!(info.is_writable, MemoryAssetError::MemoryAssetNotFound);
+
+        let mut memory_account = Account::<MemoryAccount>::try_from(info)?;
+        let covered = match scope {
+            GrantScope::Assets(asset_ids) => memory_account.asset_id == asset_ids[i],
+            // Ascending order rules out listing one memory twice
+            _ => {
+                memory_account.owner == *owner
+                    && previous.map_or(true, |previous| previous < memory_account.asset_id)
+                    && scope.covers(&memory_account)
+            }
+        };
+        require!(covered, MemoryAssetError::MemoryAssetNotFound);
+        previous = Some(memory_account.asset_id);
+
+        for share in &memory_account.royalties {
+            match combined.iter_mut().find(|(recipient, _)| *recipient == share.recipient) {
+                Some((_, bps)) => *bps += share.bps as u64,
+                None => combined.push((share.recipient, share.bps as u64)),
+            }
+        }
+        if !memory_account.royalties_locked {
+            memory_account.royalties_locked = true;
+            memory_account.exit(program_id)?;
+        }
+    }
+    require!(
+        combined.len() <= MAX_OFFER_ROYALTY_RECIPIENTS,
+        MemoryAssetError::InvalidRoyalties
+    );
+
+    let memories = memory_infos.len() as u64;
+    Ok(combined
+        .into_iter()
+        .map(|(recipient, bps)| RoyaltyShare {
+            recipient,
+            bps: (bps / memories) as u16,
+        })
+        .filter(|share| share.bps > 0)
+        .collect())
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+#[instruction(name: String, grants: Vec<AccessGrant>)]
+pub struct CreatePolicyTemplate<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = PolicyTemplate::space(&name, &grants),
+        seeds = [POLICY_TEMPLATE_SEED, authority.key().as_ref(), name.as_bytes()],
+        bump
+    )]
+    pub template: Account<'info, PolicyTemplate>,
+
+    /// User or organization that maintains the template
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<CreatePolicyTemplate>,
+    name: String,
+    grants: Vec<AccessGrant>,
+    default_policy: PolicyType,
+) -> Result<()> {
+    let template = &mut ctx.accounts.template;
+    let clock = Clock::get()?;
+
+    require!(
+        !name.is_empty() && name.len() <= MAX_TEMPLATE_NAME_LEN,
+        MemoryAssetError::InvalidTemplateName
+    );
+    PolicyTemplate::validate_grants(&grants, clock.unix_timestamp)?;
+
+    template.schema_version = PolicyTemplate::SCHEMA_VERSION;
+    template.authority = ctx.accounts.authority.key();
+    template.name = name;
+    template.grants = grants;
+    template.default_policy = default_policy;
+    template.version = 1;
+    template.created_at = clock.unix_timestamp;
+    template.updated_at = clock.unix_timestamp;
+    template.bump = ctx.bumps.template;
+
+    msg!("Policy template created");
+    msg!("Authority: {}", template.authority);
+    msg!("Name: {}", template.name);
+    msg!("Grants: {}", template.grants.len());
+    msg!("Default policy: {:?}", template.default_policy);
+
+    Ok(())
+}
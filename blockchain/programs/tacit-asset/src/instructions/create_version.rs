@@ -1,7 +1,10 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
 use crate::state::*;
+use crate::fees::{charge_storage_fee, charge_storage_fee_in_tokens};
 use crate::constants::*;
 use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
 
 #[derive(Accounts)]
 pub struct CreateVersion<'info> {
@@ -9,66 +12,155 @@ pub struct CreateVersion<'info> {
         mut,
         seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
         bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [CREDIT_SEED, owner.key().as_ref()],
+        bump = credit_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub credit_account: Account<'info, CreditAccount>,
+
+    /// CHECK: Fee recipient, validated against the config
+    #[account(mut, address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump,
         has_one = owner @ MemoryAssetError::InvalidOwner
     )]
-    pub user_account: Account<'info, UserAccount>,
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = MemoryVersionAccount::LEN,
+        seeds = [
+            MEMORY_VERSION_SEED,
+            memory_account.asset_id.as_ref(),
+            &memory_account.current_version.saturating_add(1).to_le_bytes(),
+        ],
+        bump
+    )]
+    pub version_account: Account<'info, MemoryVersionAccount>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    /// Token the storage fee is paid in; prepaid lamport credit when absent
+    #[account(seeds = [ACCEPTED_MINT_SEED, fee_mint.mint.as_ref()], bump = fee_mint.bump)]
+    pub fee_mint: Option<Account<'info, AcceptedMint>>,
+
+    /// Owner's token account paying the fee, required with `fee_mint`
+    #[account(mut)]
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Treasury token account of `fee_mint`, required with `fee_mint`
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
     pub system_program: Program<'info, System>,
 }
 
 pub fn handler(
     ctx: Context<CreateVersion>,
-    arweave_id: String,
+    storage: StorageLocator,
     content_hash: [u8; 32],
+    content_size: u64,
 ) -> Result<()> {
     let user_account = &mut ctx.accounts.user_account;
+    let memory_account = &mut ctx.accounts.memory_account;
+    let version_account = &mut ctx.accounts.version_account;
     let clock = Clock::get()?;
 
-    // Validate Arweave ID length
-    require!(
-        arweave_id.len() <= MAX_ARWEAVE_ID_LEN,
-        MemoryAssetError::InvalidArweaveIdLength
-    );
+    // Frozen or burned memories cannot be re-versioned
+    memory_account.require_mutable()?;
 
-    // Create new version record
-    let new_version = MemoryVersion {
-        version: user_account.memory_count as u32 + 1, // Simple versioning
-        arweave_id: arweave_id.clone(),
-        content_hash,
-        created_at: clock.unix_timestamp,
+    // Validate storage locator
+    storage.validate()?;
+
+    let version = memory_account
+        .current_version
+        .checked_add(1)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    // Every version is stored in full, so it counts against the quota
+    // and is paid for in an accepted token or from prepaid credit
+    user_account.record_storage(content_size, &ctx.accounts.config)?;
+    let fee = match &ctx.accounts.fee_mint {
+        Some(fee_mint) => charge_storage_fee_in_tokens(
+            fee_mint,
+            ctx.accounts.owner_token_account.as_ref(),
+            ctx.accounts.treasury_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            &ctx.accounts.owner.to_account_info(),
+            content_size,
+        )?,
+        None => charge_storage_fee(
+            &ctx.accounts.config,
+            &mut ctx.accounts.credit_account,
+            &ctx.accounts.treasury,
+            content_size,
+        )?,
     };
 
+    // Create new version record
+    version_account.schema_version = MemoryVersionAccount::SCHEMA_VERSION;
+    version_account.asset_id = memory_account.asset_id;
+    version_account.version = version;
+    version_account.content_hash = content_hash;
+    version_account.content_size = content_size;
+    version_account.locators = vec![storage.clone()];
+    version_account.redacted_at = None;
+    version_account.created_at = clock.unix_timestamp;
+    version_account.updated_at = clock.unix_timestamp;
+    version_account.bump = ctx.bumps.version_account;
+
+    memory_account.current_version = version;
+    memory_account.updated_at = clock.unix_timestamp;
+
     msg!("New version created");
     msg!("Owner: {}", ctx.accounts.owner.key());
-    msg!("Version: {}", new_version.version);
-    msg!("Arweave ID: {}", arweave_id);
+    msg!("Asset ID: {}", memory_account.asset_id);
+    msg!("Version: {}", version);
+    msg!("Storage: {:?}", storage);
     msg!("Content Hash: {:?}", content_hash);
-    msg!("Timestamp: {}", new_version.created_at);
+    msg!("Content size: {}", content_size);
+    msg!("Storage fee: {}", fee);
+    msg!("Timestamp: {}", version_account.created_at);
 
     // Emit version creation event
     emit!(VersionCreatedEvent {
         owner: ctx.accounts.owner.key(),
-        version: new_version.version,
-        arweave_id: arweave_id.clone(),
+        asset_id: memory_account.asset_id,
+        version,
+        storage,
         content_hash,
-        timestamp: new_version.created_at,
+        content_size,
+        timestamp: version_account.created_at,
     });
 
-    // Note: In a full implementation, you would store version history
-    // either in a separate account or in a vector within the user account
-    // For now, we just emit the event for off-chain indexing
-
     Ok(())
 }
 
 #[event]
 pub struct VersionCreatedEvent {
     pub owner: Pubkey,
+    pub asset_id: Pubkey,
     pub version: u32,
-    pub arweave_id: String,
+    pub storage: StorageLocator,
     pub content_hash: [u8; 32],
+    pub content_size: u64,
     pub timestamp: i64,
 }
@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
+use crate::permissions::Permissions;
+use super::update_access_policy::reserve_grant_slots;
+
+#[derive(Accounts)]
+pub struct DelegateAccess<'info> {
+    #[account(
+        mut,
+        seeds = [ACCESS_POLICY_SEED, owner.key().as_ref()],
+        bump = AccessPolicyAccount::current_bump(&access_policy)?,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    #[account(
+        mut,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    /// CHECK: Policy owner, validated by `has_one`
+    pub owner: UncheckedAccount<'info>,
+
+    /// Grantee holding `Permissions::SHARE`; pays rent when the policy needs
+    /// more grant slots
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<DelegateAccess>, grant: AccessGrant) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let policy_info = ctx.accounts.access_policy.to_account_info();
+    let owner = ctx.accounts.owner.key();
+    let delegator = ctx.accounts.delegator.key();
+    let clock = Clock::get()?;
+
+    let permissions = grant.validate(&owner, clock.unix_timestamp)?;
+
+    // Nothing is re-shared out of a locked-down policy
+    require!(
+        !ctx.accounts.access_policy.load()?.is_locked_down(),
+        MemoryAssetError::PolicyLockedDown
+    );
+
+    // Delegates hand out access, never denials or policy administration
+    require!(
+        grant.effect == GrantEffect::Allow
+            && !permissions.contains(Permissions::ADMIN)
+            && grant.grantee != delegator,
+        MemoryAssetError::InvalidDelegation
+    );
+
+    // Usage is counted from zero whatever the caller claims
+    let new_slots: Vec<GrantSlot> = grant
+        .to_slots()
+        .into_iter()
+        .map(|mut slot| {
+            slot.current_access = 0;
+            slot
+        })
+        .collect();
+
+    {
+        let data = policy_info.try_borrow_data()?;
+        let (access_policy, slots) = AccessPolicyAccount::unpack(&data);
+        let own_grants = access_policy.grants_for(slots, &delegator);
+
+        for slot in &new_slots {
+            // A deny on sharing, or on any permission handed on, anywhere
+            // the child reaches blocks it, as does a deny on the grantee
+            let handed_on = slot.permissions() | Permissions::SHARE;
+            require!(
+                !own_grants.iter().any(|deny| {
+                    deny.is_deny()
+                        && deny.is_in_effect(clock.unix_timestamp)
+                        && deny.permissions().intersects(handed_on)
+                        && (deny.scope_covers(slot) || slot.scope_kind == GrantSlot::SCOPE_ALL)
+                }) && !access_policy.is_denied(slots, slot, clock.unix_timestamp),
+                MemoryAssetError::AccessDenied
+            );
+
+            let parent = access_policy
+                .find_delegation_parent(slots, &delegator, slot, clock.unix_timestamp)
+                .ok_or(MemoryAssetError::InvalidDelegation)?;
+            require!(
+                slots[parent].depth < MAX_DELEGATION_DEPTH,
+                MemoryAssetError::DelegationDepthExceeded
+            );
+
+            // Only grants this delegator issued may be replaced
+            if let Ok(index) = access_policy.find_grant(slots, slot) {
+                require!(
+                    access_policy.is_delegated_by(slots, &delegator, &slots[index]),
+                    MemoryAssetError::Unauthorized
+                );
+            }
+        }
+    }
+
+    reserve_grant_slots(
+        &policy_info,
+        &ctx.accounts.delegator.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &new_slots,
+    )?;
+
+    let mut data = policy_info.try_borrow_mut_data()?;
+    let (access_policy, slots) = AccessPolicyAccount::unpack_mut(&mut data);
+    access_policy.capacity = slots.len() as u32;
+
+    let mut depth = 0;
+    for mut slot in new_slots {
+        // Inserts shift slots, so look the parent up again each time
+        let parent = access_policy
+            .find_delegation_parent(slots, &delegator, &slot, clock.unix_timestamp)
+            .ok_or(MemoryAssetError::InvalidDelegation)?;
+        if slots[parent].grant_id == 0 {
+            slots[parent].grant_id = access_policy.next_grant_id()?;
+        }
+        slot.parent_id = slots[parent].grant_id;
+        slot.depth = slots[parent].depth + 1;
+        depth = depth.max(slot.depth);
+        access_policy.upsert_grant(slots, slot)?;
+    }
+    access_policy.updated_at = clock.unix_timestamp;
+
+    // Increment policy version
+    user_account.access_policy_version = user_account
+        .access_policy_version
+        .checked_add(1)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    msg!("Access delegated");
+    msg!("Owner: {}", owner);
+    msg!("Delegator: {}", delegator);
+    msg!("Grantee: {}", grant.grantee);
+    msg!("Permissions: {:?}", permissions);
+    msg!("Depth: {}", depth);
+    msg!("Total grants: {}", access_policy.grant_count);
+
+    emit!(AccessDelegatedEvent {
+        owner,
+        delegator,
+        grantee: grant.grantee,
+        permissions: permissions.bits(),
+        depth,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AccessDelegatedEvent {
+    pub owner: Pubkey,
+    pub delegator: Pubkey,
+    pub grantee: Pubkey,
+    pub permissions: u8,
+    pub depth: u8,
+    pub timestamp: i64,
+}
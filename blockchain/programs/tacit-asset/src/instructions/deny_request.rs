@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use super::approve_request::AccessRequestResolvedEvent;
+use super::update_access_policy::require_policy_admin;
+
+#[derive(Accounts)]
+pub struct DenyRequest<'info> {
+    #[account(
+        mut,
+        close = requester,
+        seeds = [ACCESS_REQUEST_SEED, owner.key().as_ref(), requester.key().as_ref()],
+        bump = access_request.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        has_one = requester @ MemoryAssetError::InvalidAccessRequest
+    )]
+    pub access_request: Account<'info, AccessRequest>,
+
+    #[account(
+        seeds = [ACCESS_POLICY_SEED, owner.key().as_ref()],
+        bump = AccessPolicyAccount::current_bump(&access_policy)?,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    /// CHECK: Policy owner, validated by `has_one`
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: Requester receiving the request rent, validated by `has_one`
+    #[account(mut)]
+    pub requester: UncheckedAccount<'info>,
+
+    /// Policy owner or a grantee holding `Permissions::ADMIN`
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<DenyRequest>) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    let requester = ctx.accounts.requester.key();
+    let authority = ctx.accounts.authority.key();
+    let clock = Clock::get()?;
+
+    require_policy_admin(
+        &ctx.accounts.access_policy.to_account_info(),
+        &authority,
+        clock.unix_timestamp,
+    )?;
+
+    msg!("Access request denied");
+    msg!("Owner: {}", owner);
+    msg!("Requester: {}", requester);
+    msg!("Denied by: {}", authority);
+
+    emit!(AccessRequestResolvedEvent {
+        owner,
+        requester,
+        approved: false,
+        resolved_by: authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct DepositCredit<'info> {
+    #[account(
+        mut,
+        seeds = [CREDIT_SEED, owner.key().as_ref()],
+        bump = credit_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub credit_account: Account<'info, CreditAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<DepositCredit>, amount: u64) -> Result<()> {
+    require!(amount > 0, MemoryAssetError::InvalidAmount);
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.owner.to_account_info(),
+                to: ctx.accounts.credit_account.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let credit_account = &mut ctx.accounts.credit_account;
+    credit_account.balance = credit_account
+        .balance
+        .checked_add(amount)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+    credit_account.total_deposited = credit_account
+        .total_deposited
+        .checked_add(amount)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    msg!("Credit deposited");
+    msg!("Owner: {}", credit_account.owner);
+    msg!("Amount: {}", amount);
+    msg!("Balance: {}", credit_account.balance);
+
+    emit!(CreditDepositedEvent {
+        owner: credit_account.owner,
+        amount,
+        balance: credit_account.balance,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct CreditDepositedEvent {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub balance: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct DetachPolicyTemplate<'info> {
+    /// Grants already written stay; revoke them separately if needed
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+            TEMPLATE_BINDING_SEED,
+            owner.key().as_ref(),
+            binding.template.as_ref(),
+            binding.asset_id.unwrap_or_default().as_ref()
+        ],
+        bump = binding.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub binding: Account<'info, TemplateBinding>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<DetachPolicyTemplate>) -> Result<()> {
+    msg!("Policy template detached");
+    msg!("Owner: {}", ctx.accounts.owner.key());
+    msg!("Template: {}", ctx.accounts.binding.template);
+    msg!("Asset ID: {:?}", ctx.accounts.binding.asset_id);
+
+    Ok(())
+}
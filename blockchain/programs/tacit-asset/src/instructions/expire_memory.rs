@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::program::Bubblegum;
+use spl_account_compression::{program::SplAccountCompression, Noop};
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct ExpireMemory<'info> {
+    #[account(
+        mut,
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    /// Anyone may crank an expired memory
+    pub cranker: Signer<'info>,
+
+    /// CHECK: Merkle tree account
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Tree authority PDA
+    pub tree_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Log wrapper
+    pub log_wrapper: Program<'info, Noop>,
+
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<ExpireMemory>) -> Result<()> {
+    let memory_account = &mut ctx.accounts.memory_account;
+    let clock = Clock::get()?;
+
+    require!(
+        memory_account.status == MemoryStatus::Active,
+        MemoryAssetError::MemoryNotActive
+    );
+    require!(
+        memory_account.is_expired(clock.unix_timestamp),
+        MemoryAssetError::MemoryNotExpired
+    );
+
+    // A legal hold overrides the retention policy
+    require!(!memory_account.is_frozen(), MemoryAssetError::MemoryFrozen);
+
+    memory_account.status = MemoryStatus::Expired;
+    memory_account.updated_at = clock.unix_timestamp;
+
+    msg!("Memory expired");
+    msg!("Asset ID: {}", memory_account.asset_id);
+    msg!("Owner: {}", memory_account.owner);
+    msg!("Cranked by: {}", ctx.accounts.cranker.key());
+
+    // Note: Actual Bubblegum burn CPI call would be here
+    // In production, you would call:
+    // mpl_bubblegum::cpi::burn(...)
+
+    // Indexers and the storage gateway stop serving the content on this event
+    emit!(MemoryExpiredEvent {
+        owner: memory_account.owner,
+        asset_id: memory_account.asset_id,
+        expires_at: memory_account.expires_at.unwrap_or_default(),
+        cranker: ctx.accounts.cranker.key(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MemoryExpiredEvent {
+    pub owner: Pubkey,
+    pub asset_id: Pubkey,
+    pub expires_at: i64,
+    pub cranker: Pubkey,
+    pub timestamp: i64,
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct FreezeMemory<'info> {
+    #[account(
+        mut,
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// Memory owner or compliance authority
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<FreezeMemory>) -> Result<()> {
+    let memory_account = &mut ctx.accounts.memory_account;
+    let authority = ctx.accounts.authority.key();
+    let is_compliance = ctx.accounts.config.is_compliance_authority(&authority);
+
+    require!(
+        authority == memory_account.owner || is_compliance,
+        MemoryAssetError::Unauthorized
+    );
+    require!(
+        memory_account.status == MemoryStatus::Active,
+        MemoryAssetError::MemoryNotActive
+    );
+
+    // The compliance authority may take over an owner freeze so the owner
+    // cannot lift a legal hold; otherwise a memory is frozen only once
+    if let Some(frozen_by) = memory_account.frozen_by {
+        require!(
+            is_compliance && frozen_by != authority,
+            MemoryAssetError::MemoryFrozen
+        );
+    }
+
+    let clock = Clock::get()?;
+    memory_account.frozen_by = Some(authority);
+    memory_account.updated_at = clock.unix_timestamp;
+
+    msg!("Memory frozen");
+    msg!("Asset ID: {}", memory_account.asset_id);
+    msg!("Frozen by: {}", authority);
+
+    emit!(MemoryFrozenEvent {
+        asset_id: memory_account.asset_id,
+        authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MemoryFrozenEvent {
+    pub asset_id: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
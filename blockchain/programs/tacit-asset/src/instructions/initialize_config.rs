@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use crate::program::MemoryAsset;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = ProgramConfig::LEN,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// Program upgrade authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, MemoryAsset>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ MemoryAssetError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    config.apply(params)?;
+    config.schema_version = ProgramConfig::SCHEMA_VERSION;
+    config.updated_at = Clock::get()?.unix_timestamp;
+    config.bump = ctx.bumps.config;
+
+    msg!("Program config initialized");
+    msg!("Admin: {}", config.admin);
+    msg!("Default storage quota: {:?}", config.default_storage_quota);
+
+    Ok(())
+}
@@ -16,11 +16,20 @@ pub struct InitializeUser<'info> {
     #[account(
         init,
         payer = owner,
-        space = AccessPolicyAccount::LEN,
+        space = AccessPolicyAccount::space(INITIAL_GRANT_CAPACITY),
         seeds = [ACCESS_POLICY_SEED, owner.key().as_ref()],
         bump
     )]
-    pub access_policy: Account<'info, AccessPolicyAccount>,
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = CreditAccount::LEN,
+        seeds = [CREDIT_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub credit_account: Account<'info, CreditAccount>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
@@ -30,25 +39,39 @@ pub struct InitializeUser<'info> {
 
 pub fn handler(ctx: Context<InitializeUser>) -> Result<()> {
     let user_account = &mut ctx.accounts.user_account;
-    let access_policy = &mut ctx.accounts.access_policy;
+    let credit_account = &mut ctx.accounts.credit_account;
     let owner = &ctx.accounts.owner;
     let clock = Clock::get()?;
 
     // Initialize user account
+    user_account.schema_version = UserAccount::SCHEMA_VERSION;
     user_account.owner = owner.key();
     user_account.access_policy_version = 1;
     user_account.memory_count = 0;
     user_account.total_storage_bytes = 0;
+    user_account.storage_quota = None;
     user_account.created_at = clock.unix_timestamp;
     user_account.bump = ctx.bumps.user_account;
 
     // Initialize access policy with default deny
+    let mut access_policy = ctx.accounts.access_policy.load_init()?;
+    access_policy.schema_version = AccessPolicyAccount::SCHEMA_VERSION;
     access_policy.owner = owner.key();
-    access_policy.grants = Vec::new();
-    access_policy.default_policy = PolicyType::Deny;
+    access_policy.grant_count = 0;
+    access_policy.last_grant_id = 0;
+    access_policy.capacity = INITIAL_GRANT_CAPACITY as u32;
+    access_policy.default_policy = PolicyType::Deny as u8;
     access_policy.updated_at = clock.unix_timestamp;
     access_policy.bump = ctx.bumps.access_policy;
 
+    // Initialize empty prepaid credit
+    credit_account.schema_version = CreditAccount::SCHEMA_VERSION;
+    credit_account.owner = owner.key();
+    credit_account.balance = 0;
+    credit_account.total_deposited = 0;
+    credit_account.total_spent = 0;
+    credit_account.bump = ctx.bumps.credit_account;
+
     msg!("User account initialized for: {}", owner.key());
     msg!("Memory count: {}", user_account.memory_count);
     msg!("Access policy version: {}", user_account.access_policy_version);
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::program::Bubblegum;
+use spl_account_compression::{program::SplAccountCompression, Noop};
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct ListMemory<'info> {
+    #[account(
+        init,
+        payer = seller,
+        space = Listing::LEN,
+        seeds = [LISTING_SEED, memory_account.asset_id.as_ref()],
+        bump
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(
+        mut,
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump,
+        constraint = memory_account.owner == seller.key() @ MemoryAssetError::InvalidOwner
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    /// Owner of the memory; pays the listing rent
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    /// CHECK: Merkle tree account
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Tree authority PDA
+    pub tree_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Log wrapper
+    pub log_wrapper: Program<'info, Noop>,
+
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub bubblegum_program: Program<'info, Bubblegum>,
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: proof path of the compressed NFT leaf
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ListMemory<'info>>,
+    price: u64,
+    allowed_buyer: Option<Pubkey>,
+    expires_at: i64,
+    leaf: CompressedLeaf,
+) -> Result<()> {
+    let memory_account = &mut ctx.accounts.memory_account;
+    let seller = ctx.accounts.seller.key();
+    let clock = Clock::get()?;
+
+    require!(price > 0, MemoryAssetError::InvalidAmount);
+    require!(
+        allowed_buyer != Some(seller),
+        MemoryAssetError::InvalidOwner
+    );
+    require!(
+        expires_at > clock.unix_timestamp,
+        MemoryAssetError::InvalidExpiry
+    );
+
+    // Frozen, burned or already listed memories cannot be listed
+    memory_account.require_mutable()?;
+    leaf.require_asset(&ctx.accounts.merkle_tree.key(), &memory_account.asset_id)?;
+
+    let listing = &mut ctx.accounts.listing;
+    listing.schema_version = Listing::SCHEMA_VERSION;
+    listing.asset_id = memory_account.asset_id;
+    listing.seller = seller;
+    listing.price = price;
+    listing.allowed_buyer = allowed_buyer;
+    listing.expires_at = expires_at;
+    listing.created_at = clock.unix_timestamp;
+    listing.bump = ctx.bumps.listing;
+
+    memory_account.delegate = Some(listing.key());
+    memory_account.updated_at = clock.unix_timestamp;
+
+    msg!("Memory listed");
+    msg!("Asset ID: {}", memory_account.asset_id);
+    msg!("Seller: {}", seller);
+    msg!("Price: {}", price);
+    if let Some(allowed_buyer) = allowed_buyer {
+        msg!("Reserved for: {}", allowed_buyer);
+    }
+    msg!("Expires at: {}", expires_at);
+
+    // Make the listing PDA the leaf delegate: the seller can no longer move
+    // the compressed NFT while it is for sale, and the sale can hand it over
+    mpl_bubblegum::cpi::delegate(
+        CpiContext::new(
+            ctx.accounts.bubblegum_program.to_account_info(),
+            mpl_bubblegum::cpi::accounts::Delegate {
+                tree_authority: ctx.accounts.tree_authority.to_account_info(),
+                leaf_owner: ctx.accounts.seller.to_account_info(),
+                previous_leaf_delegate: ctx.accounts.seller.to_account_info(),
+                new_leaf_delegate: listing.to_account_info(),
+                merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+                compression_program: ctx.accounts.compression_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        leaf.root,
+        leaf.data_hash,
+        leaf.creator_hash,
+        leaf.nonce,
+        leaf.index,
+    )?;
+
+    emit!(MemoryListedEvent {
+        asset_id: memory_account.asset_id,
+        listing: listing.key(),
+        seller,
+        price,
+        allowed_buyer,
+        expires_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MemoryListedEvent {
+    pub asset_id: Pubkey,
+    pub listing: Pubkey,
+    pub seller: Pubkey,
+    pub price: u64,
+    pub allowed_buyer: Option<Pubkey>,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
+
+#[derive(Accounts)]
+pub struct Lockdown<'info> {
+    #[account(
+        mut,
+        seeds = [ACCESS_POLICY_SEED, owner.key().as_ref()],
+        bump = AccessPolicyAccount::current_bump(&access_policy)?,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    #[account(
+        mut,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    /// Only the owner can lock down; admins may be the compromised party
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    // remaining_accounts: writable AgentAccounts of this owner to revoke
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, Lockdown<'info>>) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let owner = ctx.accounts.owner.key();
+    let clock = Clock::get()?;
+
+    // Every grant goes, including delegations and deny grants, and nothing
+    // is readable by default afterwards
+    let grants_removed = {
+        let policy_info = ctx.accounts.access_policy.to_account_info();
+        let mut data = policy_info.try_borrow_mut_data()?;
+        let (access_policy, slots) = AccessPolicyAccount::unpack_mut(&mut data);
+        let removed = access_policy.remove_grants(slots, |_| true);
+        access_policy.default_policy = PolicyType::Deny as u8;
+        access_policy.locked_down = 1;
+        access_policy.updated_at = clock.unix_timestamp;
+        removed as u32
+    };
+
+    // Agent keys sign on the owner's behalf, so they are revoked as well
+    let mut agents_revoked = Vec::with_capacity(ctx.remaining_accounts.len());
+    for info in ctx.remaining_accounts.iter() {
+        require!(info.is_writable, MemoryAssetError::InvalidAgentAccount);
+
+        let agent_account = Account::<AgentAccount>::try_from(info)?;
+        require!(
+            agent_account.owner == owner,
+            MemoryAssetError::InvalidAgentAccount
+        );
+        agents_revoked.push(agent_account.agent);
+        agent_account.close(ctx.accounts.owner.to_account_info())?;
+    }
+
+    // Off-chain grants signed against an older version stop verifying
+    user_account.access_policy_version = user_account
+        .access_policy_version
+        .checked_add(1)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    msg!("Lockdown");
+    msg!("Owner: {}", owner);
+    msg!("Grants removed: {}", grants_removed);
+    msg!("Agents revoked: {:?}", agents_revoked);
+    msg!("Policy version: {}", user_account.access_policy_version);
+
+    emit!(LockdownEvent {
+        owner,
+        grants_removed,
+        agents_revoked,
+        policy_version: user_account.access_policy_version,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct LockdownEvent {
+    pub owner: Pubkey,
+    pub grants_removed: u32,
+    pub agents_revoked: Vec<Pubkey>,
+    pub policy_version: u32,
+    pub timestamp: i64,
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::{
+    resize_account, AccountMigratedEvent, AccessPolicyAccountV0, AccessPolicyAccountV1,
+};
+
+#[derive(Accounts)]
+pub struct MigrateAccessPolicy<'info> {
+    /// CHECK: Legacy layouts do not load as `AccessPolicyAccount`, so the
+    /// data is decoded manually in the handler
+    #[account(
+        mut,
+        seeds = [ACCESS_POLICY_SEED, owner.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub access_policy: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateAccessPolicy>) -> Result<()> {
+    let account_info = ctx.accounts.access_policy.to_account_info();
+
+    let (from_version, legacy) = {
+        let data = account_info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == AccessPolicyAccount::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+
+        // Legacy layouts are identified by their allocated size
+        match data.len() {
+            AccessPolicyAccountV0::LEN => {
+                let legacy = AccessPolicyAccountV0::deserialize(&mut &data[8..])?;
+                (0, legacy.upgrade())
+            }
+            AccessPolicyAccountV1::LEN => {
+                let legacy = AccessPolicyAccountV1::deserialize(&mut &data[8..])?;
+                (1, legacy)
+            }
+            _ => return err!(MemoryAssetError::AlreadyMigrated),
+        }
+    };
+
+    require!(
+        legacy.owner == ctx.accounts.owner.key(),
+        MemoryAssetError::InvalidOwner
+    );
+
+    let owner = legacy.owner;
+    let grant_count = legacy.grants.len();
+    resize_account(
+        &account_info,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        AccessPolicyAccount::space(legacy.capacity()),
+    )?;
+    legacy.write_current(&mut account_info.try_borrow_mut_data()?);
+
+    msg!("Access policy migrated");
+    msg!("Owner: {}", owner);
+    msg!("Grants: {}", grant_count);
+    msg!(
+        "Schema version: {} -> {}",
+        from_version,
+        AccessPolicyAccount::SCHEMA_VERSION
+    );
+
+    emit!(AccountMigratedEvent {
+        account: account_info.key(),
+        owner,
+        from_version,
+        to_version: AccessPolicyAccount::SCHEMA_VERSION,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct MigrateStorage<'info> {
+    #[account(
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            MEMORY_VERSION_SEED,
+            memory_account.asset_id.as_ref(),
+            &version_account.version.to_le_bytes(),
+        ],
+        bump = version_account.bump
+    )]
+    pub version_account: Account<'info, MemoryVersionAccount>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<MigrateStorage>,
+    new_storage: StorageLocator,
+    content_hash: [u8; 32],
+) -> Result<()> {
+    let version_account = &mut ctx.accounts.version_account;
+    let clock = Clock::get()?;
+
+    // Frozen or burned memories keep their locators as they are
+    ctx.accounts.memory_account.require_active()?;
+
+    // Redacted content must never be pointed at again
+    require!(!version_account.is_redacted(), MemoryAssetError::ContentRedacted);
+
+    // The content must be byte-identical at the new location
+    require!(
+        version_account.content_hash == content_hash,
+        MemoryAssetError::ContentHashMismatch
+    );
+
+    new_storage.validate()?;
+
+    let old_storage = version_account
+        .primary()
+        .cloned()
+        .ok_or(MemoryAssetError::InvalidVersion)?;
+    require!(
+        new_storage != old_storage,
+        MemoryAssetError::DuplicateStorageLocator
+    );
+
+    // Promote the new locator to primary, dropping it from the mirrors if present
+    version_account.locators.retain(|locator| *locator != new_storage);
+    version_account.locators[0] = new_storage.clone();
+    version_account.updated_at = clock.unix_timestamp;
+
+    msg!("Storage migrated");
+    msg!("Asset ID: {}", version_account.asset_id);
+    msg!("Version: {}", version_account.version);
+    msg!("From: {:?}", old_storage);
+    msg!("To: {:?}", new_storage);
+
+    emit!(StorageMigratedEvent {
+        asset_id: version_account.asset_id,
+        version: version_account.version,
+        content_hash,
+        old_storage,
+        new_storage,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct StorageMigratedEvent {
+    pub asset_id: Pubkey,
+    pub version: u32,
+    pub content_hash: [u8; 32],
+    pub old_storage: StorageLocator,
+    pub new_storage: StorageLocator,
+    pub timestamp: i64,
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::{rewrite_account, AccountMigratedEvent, UserAccountV0};
+
+#[derive(Accounts)]
+pub struct MigrateUserAccount<'info> {
+    /// CHECK: Legacy layouts do not deserialize as `UserAccount`, so the
+    /// data is decoded manually in the handler
+    #[account(
+        mut,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub user_account: UncheckedAccount<'info>,
+
+    /// Legacy users predate prepaid credit, so it is created here
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = CreditAccount::LEN,
+        seeds = [CREDIT_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub credit_account: Account<'info, CreditAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateUserAccount>) -> Result<()> {
+    let account_info = ctx.accounts.user_account.to_account_info();
+
+    let (from_version, migrated) = {
+        let data = account_info.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == UserAccount::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+
+        // Legacy layouts are identified by their allocated size
+        match data.len() {
+            UserAccountV0::LEN => {
+                let legacy = UserAccountV0::deserialize(&mut &data[8..])?;
+                (0, legacy.upgrade())
+            }
+            _ => return err!(MemoryAssetError::AlreadyMigrated),
+        }
+    };
+
+    require!(
+        migrated.owner == ctx.accounts.owner.key(),
+        MemoryAssetError::InvalidOwner
+    );
+
+    rewrite_account(
+        &account_info,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        UserAccount::LEN,
+        &migrated,
+    )?;
+
+    let credit_account = &mut ctx.accounts.credit_account;
+    if credit_account.owner == Pubkey::default() {
+        credit_account.schema_version = CreditAccount::SCHEMA_VERSION;
+        credit_account.owner = migrated.owner;
+        credit_account.balance = 0;
+        credit_account.total_deposited = 0;
+        credit_account.total_spent = 0;
+        credit_account.bump = ctx.bumps.credit_account;
+    }
+
+    msg!("User account migrated");
+    msg!("Owner: {}", migrated.owner);
+    msg!("Schema version: {} -> {}", from_version, migrated.schema_version);
+
+    emit!(AccountMigratedEvent {
+        account: account_info.key(),
+        owner: migrated.owner,
+        from_version,
+        to_version: migrated.schema_version,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
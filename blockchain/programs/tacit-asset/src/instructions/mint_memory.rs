@@ -1,20 +1,58 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::Mint;
+use anchor_lang::solana_program::{ed25519_program, sysvar::instructions as sysvar_instructions};
+use anchor_spl::token::{Token, TokenAccount};
 use mpl_bubblegum::program::Bubblegum;
 use spl_account_compression::{program::SplAccountCompression, Noop};
 use crate::state::*;
+use crate::fees::{charge_storage_fee, charge_storage_fee_in_tokens};
 use crate::constants::*;
 use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
 
 #[derive(Accounts)]
+#[instruction(asset_id: Pubkey)]
 pub struct MintMemory<'info> {
     #[account(
         mut,
         seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
         bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [CREDIT_SEED, owner.key().as_ref()],
+        bump = credit_account.bump,
         has_one = owner @ MemoryAssetError::InvalidOwner
     )]
-    pub user_account: Account<'info, UserAccount>,
+    pub credit_account: Account<'info, CreditAccount>,
+
+    /// CHECK: Fee recipient, validated against the config
+    #[account(mut, address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = MemoryAccount::LEN,
+        seeds = [MEMORY_SEED, asset_id.as_ref()],
+        bump
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = MemoryVersionAccount::LEN,
+        seeds = [MEMORY_VERSION_SEED, asset_id.as_ref(), &DEFAULT_VERSION.to_le_bytes()],
+        bump
+    )]
+    pub version_account: Account<'info, MemoryVersionAccount>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
@@ -29,6 +67,32 @@ pub struct MintMemory<'info> {
     /// CHECK: Bubblegum signer PDA
     pub bubblegum_signer: UncheckedAccount<'info>,
 
+    /// Registered agent whose signature over the content is required, if any
+    #[account(
+        seeds = [AGENT_SEED, owner.key().as_ref(), agent_account.agent.as_ref()],
+        bump = agent_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub agent_account: Option<Account<'info, AgentAccount>>,
+
+    /// CHECK: Instructions sysvar, required when agent_account is provided
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+
+    /// Token the storage fee is paid in; prepaid lamport credit when absent
+    #[account(seeds = [ACCEPTED_MINT_SEED, fee_mint.mint.as_ref()], bump = fee_mint.bump)]
+    pub fee_mint: Option<Account<'info, AcceptedMint>>,
+
+    /// Owner's token account paying the fee, required with `fee_mint`
+    #[account(mut)]
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// Treasury token account of `fee_mint`, required with `fee_mint`
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
     /// CHECK: Log wrapper for compression
     pub log_wrapper: Program<'info, Noop>,
 
@@ -39,52 +103,163 @@ pub struct MintMemory<'info> {
 
 pub fn handler(
     ctx: Context<MintMemory>,
-    arweave_id: String,
+    asset_id: Pubkey,
+    storage: StorageLocator,
     content_hash: [u8; 32],
+    content_size: u64,
     metadata_uri: String,
+    signed_at: Option<i64>,
+    expires_at: Option<i64>,
+    batch_id: Option<String>,
+    memory_type: Option<String>,
 ) -> Result<()> {
     let user_account = &mut ctx.accounts.user_account;
     let clock = Clock::get()?;
 
     // Validate inputs
-    require!(
-        arweave_id.len() <= MAX_ARWEAVE_ID_LEN,
-        MemoryAssetError::InvalidArweaveIdLength
-    );
+    storage.validate()?;
     require!(
         metadata_uri.len() <= MAX_METADATA_URI_LEN,
         MemoryAssetError::InvalidMetadataUriLength
     );
+    if let Some(batch_id) = &batch_id {
+        require!(
+            batch_id.len() <= MAX_BATCH_ID_LEN,
+            MemoryAssetError::InvalidBatchIdLength
+        );
+    }
+    if let Some(memory_type) = &memory_type {
+        require!(
+            memory_type.len() <= MAX_MEMORY_TYPE_LEN,
+            MemoryAssetError::InvalidMemoryTypeLength
+        );
+    }
+    if let Some(expires_at) = expires_at {
+        require!(
+            expires_at > clock.unix_timestamp,
+            MemoryAssetError::InvalidExpiry
+        );
+    }
+
+    // Verify agent provenance signature if an agent is attached
+    let agent = match &ctx.accounts.agent_account {
+        Some(agent_account) => {
+            let signed_at = signed_at.ok_or(MemoryAssetError::MissingAgentSignature)?;
+            require!(
+                signed_at <= clock.unix_timestamp
+                    && clock.unix_timestamp - signed_at <= MAX_AGENT_SIGNATURE_AGE,
+                MemoryAssetError::AgentSignatureExpired
+            );
+
+            let instructions_sysvar = ctx
+                .accounts
+                .instructions_sysvar
+                .as_ref()
+                .ok_or(MemoryAssetError::MissingAgentSignature)?;
+            let message = agent_signature_message(
+                &ctx.accounts.owner.key(),
+                &asset_id,
+                &content_hash,
+                &storage,
+                signed_at,
+            )?;
+            verify_agent_signature(instructions_sysvar, &agent_account.agent, &message)?;
+
+            Some(agent_account.agent)
+        }
+        None => None,
+    };
 
     // Create memory metadata
     let memory_metadata = MemoryMetadata {
         owner: ctx.accounts.owner.key(),
-        arweave_id: arweave_id.clone(),
+        storage: storage.clone(),
         version: DEFAULT_VERSION,
-        batch_id: None,
+        batch_id: batch_id.clone(),
         created_at: clock.unix_timestamp,
         content_hash,
+        content_size,
+        agent,
+        expires_at,
+        memory_type: memory_type.clone(),
     };
 
     // Serialize metadata for Bubblegum
     let metadata_bytes = memory_metadata.try_to_vec()?;
 
+    // Record the memory and its first version
+    let memory_account = &mut ctx.accounts.memory_account;
+    memory_account.schema_version = MemoryAccount::SCHEMA_VERSION;
+    memory_account.asset_id = asset_id;
+    memory_account.owner = ctx.accounts.owner.key();
+    memory_account.creator = ctx.accounts.owner.key();
+    memory_account.current_version = DEFAULT_VERSION;
+    memory_account.batch_id = batch_id;
+    memory_account.memory_type = memory_type;
+    memory_account.status = MemoryStatus::Active;
+    memory_account.frozen_by = None;
+    memory_account.expires_at = expires_at;
+    memory_account.royalties = Vec::new();
+    memory_account.royalties_locked = false;
+    memory_account.delegate = None;
+    memory_account.created_at = clock.unix_timestamp;
+    memory_account.updated_at = clock.unix_timestamp;
+    memory_account.bump = ctx.bumps.memory_account;
+
+    let version_account = &mut ctx.accounts.version_account;
+    version_account.schema_version = MemoryVersionAccount::SCHEMA_VERSION;
+    version_account.asset_id = asset_id;
+    version_account.version = DEFAULT_VERSION;
+    version_account.content_hash = content_hash;
+    version_account.content_size = content_size;
+    version_account.locators = vec![storage.clone()];
+    version_account.redacted_at = None;
+    version_account.created_at = clock.unix_timestamp;
+    version_account.updated_at = clock.unix_timestamp;
+    version_account.bump = ctx.bumps.version_account;
+
     // Update user account statistics
     user_account.memory_count = user_account
         .memory_count
         .checked_add(1)
         .ok_or(MemoryAssetError::ArithmeticOverflow)?;
-    
-    user_account.total_storage_bytes = user_account
-        .total_storage_bytes
-        .checked_add(metadata_bytes.len() as u64)
-        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    user_account.record_storage(content_size, &ctx.accounts.config)?;
+
+    // Pay for storage in an accepted token, or from prepaid credit
+    let fee = match &ctx.accounts.fee_mint {
+        Some(fee_mint) => charge_storage_fee_in_tokens(
+            fee_mint,
+            ctx.accounts.owner_token_account.as_ref(),
+            ctx.accounts.treasury_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+            &ctx.accounts.owner.to_account_info(),
+            content_size,
+        )?,
+        None => charge_storage_fee(
+            &ctx.accounts.config,
+            &mut ctx.accounts.credit_account,
+            &ctx.accounts.treasury,
+            content_size,
+        )?,
+    };
 
     msg!("Memory minted successfully");
     msg!("Owner: {}", ctx.accounts.owner.key());
-    msg!("Arweave ID: {}", arweave_id);
+    msg!("Asset ID: {}", asset_id);
+    msg!("Storage: {:?}", storage);
     msg!("Content Hash: {:?}", content_hash);
+    msg!("Content size: {}", content_size);
+    msg!("Storage fee: {}", fee);
+    msg!("Leaf metadata size: {}", metadata_bytes.len());
     msg!("Total memories: {}", user_account.memory_count);
+    msg!("Total storage bytes: {}", user_account.total_storage_bytes);
+    if let Some(agent) = agent {
+        msg!("Signed by agent: {}", agent);
+    }
+    if let Some(expires_at) = expires_at {
+        msg!("Expires at: {}", expires_at);
+    }
 
     // Note: Actual Bubblegum CPI call would be here
     // For now, we're just updating our state
@@ -93,3 +268,78 @@ pub fn handler(
 
     Ok(())
 }
+
+/// Build the message an agent signs:
+/// owner || asset_id || content_hash || borsh(storage) || signed_at (LE)
+pub fn agent_signature_message(
+    owner: &Pubkey,
+    asset_id: &Pubkey,
+    content_hash: &[u8; 32],
+    storage: &StorageLocator,
+    signed_at: i64,
+) -> Result<Vec<u8>> {
+    let storage_bytes = storage.try_to_vec()?;
+    let mut message = Vec::with_capacity(32 + 32 + 32 + storage_bytes.len() + 8);
+    message.extend_from_slice(owner.as_ref());
+    message.extend_from_slice(asset_id.as_ref());
+    message.extend_from_slice(content_hash);
+    message.extend_from_slice(&storage_bytes);
+    message.extend_from_slice(&signed_at.to_le_bytes());
+    Ok(message)
+}
+
+/// Helper function to check that the preceding instruction is an Ed25519
+/// signature verification by `agent` over `message`
+pub fn verify_agent_signature(
+    instructions_sysvar: &AccountInfo,
+    agent: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    // The Ed25519 program instruction must immediately precede this one
+    let current_index = sysvar_instructions::load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, MemoryAssetError::MissingAgentSignature);
+    let ed25519_ix = sysvar_instructions::load_instruction_at_checked(
+        (current_index - 1) as usize,
+        instructions_sysvar,
+    )?;
+    require!(
+        ed25519_ix.program_id == ed25519_program::ID,
+        MemoryAssetError::MissingAgentSignature
+    );
+
+    // Layout: num_signatures (u8), padding (u8), then one 14-byte offsets struct
+    let data = &ed25519_ix.data;
+    require!(
+        data.len() >= 16 && data[0] == 1,
+        MemoryAssetError::InvalidAgentSignature
+    );
+    let read_u16 = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+    let public_key_offset = read_u16(6) as usize;
+    let public_key_ix_index = read_u16(8);
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+    let message_ix_index = read_u16(14);
+    let signature_ix_index = read_u16(4);
+
+    // All signature data must live in the Ed25519 instruction itself
+    require!(
+        signature_ix_index == u16::MAX
+            && public_key_ix_index == u16::MAX
+            && message_ix_index == u16::MAX,
+        MemoryAssetError::InvalidAgentSignature
+    );
+
+    let signed_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(MemoryAssetError::InvalidAgentSignature)?;
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(MemoryAssetError::InvalidAgentSignature)?;
+
+    require!(
+        signed_key == agent.as_ref() && signed_message == message,
+        MemoryAssetError::InvalidAgentSignature
+    );
+
+    Ok(())
+}
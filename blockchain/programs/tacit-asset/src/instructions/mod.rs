@@ -3,9 +3,91 @@ pub mod mint_memory;
 pub mod update_access_policy;
 pub mod transfer_memory;
 pub mod create_version;
+pub mod attest_memory;
+pub mod revoke_attestation;
+pub mod register_agent;
+pub mod revoke_agent;
+pub mod set_storage_mirrors;
+pub mod migrate_storage;
+pub mod initialize_config;
+pub mod update_config;
+pub mod set_storage_quota;
+pub mod deposit_credit;
+pub mod close_user;
+pub mod migrate_user_account;
+pub mod migrate_access_policy;
+pub mod freeze_memory;
+pub mod thaw_memory;
+pub mod burn_memory;
+pub mod expire_memory;
+pub mod redact_memory;
+pub mod revoke_access_grants;
+pub mod delegate_access;
+pub mod prune_grants;
+pub mod lockdown;
+pub mod create_policy_template;
+pub mod update_policy_template;
+pub mod apply_policy_template;
+pub mod sync_policy_template;
+pub mod detach_policy_template;
+pub mod request_access;
+pub mod approve_request;
+pub mod deny_request;
+pub mod create_access_offer;
+pub mod close_access_offer;
+pub mod purchase_access;
+pub mod add_accepted_mint;
+pub mod remove_accepted_mint;
+pub mod set_royalties;
+pub mod list_memory;
+pub mod cancel_listing;
+pub mod buy_memory;
+pub mod record_access;
+pub mod cancel_request;
 
 pub use initialize_user::*;
 pub use mint_memory::*;
 pub use update_access_policy::*;
 pub use transfer_memory::*;
 pub use create_version::*;
+pub use attest_memory::*;
+pub use revoke_attestation::*;
+pub use register_agent::*;
+pub use revoke_agent::*;
+pub use set_storage_mirrors::*;
+pub use migrate_storage::*;
+pub use initialize_config::*;
+pub use update_config::*;
+pub use set_storage_quota::*;
+pub use deposit_credit::*;
+pub use close_user::*;
+pub use migrate_user_account::*;
+pub use migrate_access_policy::*;
+pub use freeze_memory::*;
+pub use thaw_memory::*;
+pub use burn_memory::*;
+pub use expire_memory::*;
+pub use redact_memory::*;
+pub use revoke_access_grants::*;
+pub use delegate_access::*;
+pub use prune_grants::*;
+pub use lockdown::*;
+pub use create_policy_template::*;
+pub use update_policy_template::*;
+pub use apply_policy_template::*;
+pub use sync_policy_template::*;
+pub use detach_policy_template::*;
+pub use request_access::*;
+pub use approve_request::*;
+pub use deny_request::*;
+pub use create_access_offer::*;
+pub use close_access_offer::*;
+pub use purchase_access::*;
+pub use add_accepted_mint::*;
+pub use remove_accepted_mint::*;
+pub use set_royalties::*;
+pub use list_memory::*;
+pub use cancel_listing::*;
+pub use buy_memory::*;
+pub use record_access::*;
+pub use cancel_request::*;
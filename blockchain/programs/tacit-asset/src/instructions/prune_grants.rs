@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
+use crate::fees::pay_prune_bounty;
+
+#[derive(Accounts)]
+pub struct PruneGrants<'info> {
+    #[account(
+        mut,
+        seeds = [ACCESS_POLICY_SEED, owner.key().as_ref()],
+        bump = AccessPolicyAccount::current_bump(&access_policy)?,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    #[account(
+        mut,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [CREDIT_SEED, owner.key().as_ref()],
+        bump = credit_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub credit_account: Account<'info, CreditAccount>,
+
+    /// CHECK: Policy owner, validated by `has_one`
+    pub owner: UncheckedAccount<'info>,
+
+    /// Anyone may prune dead grants and collect the bounty
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<PruneGrants>) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let owner = ctx.accounts.owner.key();
+    let cranker = ctx.accounts.cranker.key();
+    let clock = Clock::get()?;
+
+    let (grants_pruned, grants_bountied, grant_count) = {
+        let policy_info = ctx.accounts.access_policy.to_account_info();
+        let mut data = policy_info.try_borrow_mut_data()?;
+        let (access_policy, slots) = AccessPolicyAccount::unpack_mut(&mut data);
+
+        // Only owner-issued and purchased grants earn the bounty; delegates
+        // could otherwise farm it with short-lived re-shares
+        let bountied = access_policy
+            .grants(slots)
+            .iter()
+            .filter(|slot| slot.depth == 0 && slot.is_prunable(clock.unix_timestamp))
+            .count();
+
+        // Delegations hanging off a pruned grant go with it
+        let removed = access_policy
+            .remove_grants(slots, |slot| slot.is_prunable(clock.unix_timestamp))
+            + access_policy.prune_orphans(slots);
+        require!(removed > 0, MemoryAssetError::NothingToPrune);
+
+        access_policy.updated_at = clock.unix_timestamp;
+        (removed as u32, bountied as u32, access_policy.grant_count)
+    };
+
+    // Increment policy version
+    user_account.access_policy_version = user_account
+        .access_policy_version
+        .checked_add(1)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    let bounty = pay_prune_bounty(
+        &ctx.accounts.config,
+        &mut ctx.accounts.credit_account,
+        &ctx.accounts.cranker.to_account_info(),
+        grants_bountied,
+    )?;
+
+    msg!("Access grants pruned");
+    msg!("Owner: {}", owner);
+    msg!("Pruned by: {}", cranker);
+    msg!("Grants pruned: {}", grants_pruned);
+    msg!("Total grants: {}", grant_count);
+    msg!("Bounty: {} lamports", bounty);
+
+    emit!(GrantsPrunedEvent {
+        owner,
+        cranker,
+        grants_pruned,
+        bounty,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct GrantsPrunedEvent {
+    pub owner: Pubkey,
+    pub cranker: Pubkey,
+    pub grants_pruned: u32,
+    pub bounty: u64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,354 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
+use crate::fees::{pay_royalties, pay_royalties_in_tokens, require_token_account, transfer_tokens};
+use super::update_access_policy::reserve_grant_slots;
+
+#[derive(Accounts)]
+pub struct PurchaseAccess<'info> {
+    #[account(
+        mut,
+        seeds = [ACCESS_OFFER_SEED, owner.key().as_ref(), &access_offer.offer_id.to_le_bytes()],
+        bump = access_offer.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub access_offer: Account<'info, AccessOffer>,
+
+    #[account(
+        mut,
+        seeds = [ACCESS_POLICY_SEED, owner.key().as_ref()],
+        bump = AccessPolicyAccount::current_bump(&access_policy)?,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    #[account(
+        mut,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// CHECK: Protocol fee recipient, validated against the config
+    #[account(mut, address = config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Seller receiving the proceeds, validated by `has_one`
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+
+    /// Buyer; pays the price and rent when the policy needs more grant slots
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// Payment mint of token-priced offers
+    #[account(
+        seeds = [ACCEPTED_MINT_SEED, accepted_mint.mint.as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Option<Account<'info, AcceptedMint>>,
+
+    #[account(mut)]
+    pub buyer_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub treasury_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: for asset offers, the MemoryAccount of every scoped
+    // asset in scope order; then the writable recipient (or recipient token
+    // account) of each of the offer's royalty shares, in order
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PurchaseAccess<'info>>,
+) -> Result<()> {
+    let policy_info = ctx.accounts.access_policy.to_account_info();
+    let owner = ctx.accounts.owner.key();
+    let buyer = ctx.accounts.buyer.key();
+    let clock = Clock::get()?;
+
+    // Access cannot be bought back into a locked-down policy
+    require!(
+        !ctx.accounts.access_policy.load()?.is_locked_down(),
+        MemoryAssetError::PolicyLockedDown
+    );
+
+    let offer = &ctx.accounts.access_offer;
+    let price = offer.price;
+    let protocol_fee = ctx.accounts.config.protocol_fee(price)?;
+    let proceeds = price - protocol_fee;
+
+    let mut grant = AccessGrant {
+        grantee: buyer,
+        permissions: offer.permissions,
+        expires_at: None,
+        max_access: offer.max_access,
+        current_access: 0,
+        scope: offer.scope.clone(),
+        effect: GrantEffect::Allow,
+        not_before: None,
+        quota: None,
+    };
+    let mut new_slots = grant.to_slots();
+    for slot in new_slots.iter_mut() {
+        slot.origin = GrantSlot::ORIGIN_PURCHASE;
+    }
+    // Access to specific memories is only sold while they are all active
+    let recipients = require_active_assets(ctx.program_id, &offer.scope, ctx.remaining_accounts)?;
+
+    // Royalties follow the schedule fixed when the offer was created
+    let payouts = offer.royalty_payouts(proceeds)?;
+
+    // Buying again before expiry extends the grant rather than resetting it.
+    // Paying never lifts a deny or replaces a grant the owner issued.
+    let expires_at = {
+        let data = policy_info.try_borrow_data()?;
+        let (access_policy, slots) = AccessPolicyAccount::unpack(&data);
+        for slot in &new_slots {
+            require!(
+                !access_policy.is_denied(slots, slot, clock.unix_timestamp),
+                MemoryAssetError::AccessDenied
+            );
+            if let Ok(index) = access_policy.find_grant(slots, slot) {
+                require!(
+                    slots[index].origin == GrantSlot::ORIGIN_PURCHASE,
+                    MemoryAssetError::AccessGrantConflict
+                );
+            }
+        }
+        let start = new_slots
+            .iter()
+            .filter_map(|slot| access_policy.find_grant(slots, slot).ok())
+            .map(|index| &slots[index])
+            .filter(|existing| {
+                !existing.is_deny()
+                    && existing.permissions == offer.permissions
+                    && existing.expires_at > clock.unix_timestamp
+            })
+            .map(|existing| existing.expires_at)
+            .min()
+            .unwrap_or(clock.unix_timestamp);
+        start
+            .checked_add(offer.duration)
+            .ok_or(MemoryAssetError::ArithmeticOverflow)?
+    };
+    grant.expires_at = Some(expires_at);
+    grant.validate(&owner, clock.unix_timestamp)?;
+    for slot in new_slots.iter_mut() {
+        slot.expires_at = expires_at;
+    }
+
+    reserve_grant_slots(
+        &policy_info,
+        &ctx.accounts.buyer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &new_slots,
+    )?;
+
+    // Payment and grant land in the same transaction
+    let royalties = match offer.payment_mint {
+        Some(payment_mint) => {
+            pay_in_tokens(&ctx.accounts, payment_mint, protocol_fee, proceeds, &payouts, recipients)?
+        }
+        None => pay_in_lamports(&ctx.accounts, protocol_fee, proceeds, &payouts, recipients)?,
+    };
+
+    {
+        let mut data = policy_info.try_borrow_mut_data()?;
+        let (access_policy, slots) = AccessPolicyAccount::unpack_mut(&mut data);
+        access_policy.capacity = slots.len() as u32;
+        for slot in new_slots {
+            access_policy.upsert_grant(slots, slot)?;
+        }
+        // A replaced purchase may have lost permissions its delegations need
+        let orphans_removed = access_policy.prune_orphans(slots);
+        if orphans_removed > 0 {
+            msg!("Delegations removed: {}", orphans_removed);
+        }
+        access_policy.updated_at = clock.unix_timestamp;
+    }
+
+    // Increment policy version
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.access_policy_version = user_account
+        .access_policy_version
+        .checked_add(1)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    let access_offer = &mut ctx.accounts.access_offer;
+    access_offer.sales = access_offer
+        .sales
+        .checked_add(1)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    msg!("Access purchased");
+    msg!("Owner: {}", owner);
+    msg!("Buyer: {}", buyer);
+    msg!("Offer ID: {}", access_offer.offer_id);
+    msg!("Price: {}", price);
+    msg!("Protocol fee: {}", protocol_fee);
+    msg!("Royalties: {}", royalties);
+    msg!("Payment mint: {:?}", access_offer.payment_mint);
+    msg!("Expires at: {}", expires_at);
+
+    emit!(AccessPurchasedEvent {
+        owner,
+        buyer,
+        offer: access_offer.key(),
+        price,
+        payment_mint: access_offer.payment_mint,
+        protocol_fee,
+        royalties,
+        expires_at,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Check the MemoryAccounts leading `remaining_accounts` are the asset
+/// offer's memories, in scope order, and all active, returning the accounts
+/// after them
+fn require_active_assets<'info>(
+    program_id: &Pubkey,
+    scope: &GrantScope,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<&'info [AccountInfo<'info>]> {
+    let asset_ids = match scope {
+        GrantScope::Assets(asset_ids) => asset_ids,
+        _ => return Ok(remaining_accounts),
+    };
+    require!(
+        remaining_accounts.len() >= asset_ids.len(),
+        MemoryAssetError::MemoryAssetNotFound
+    );
+
+    let (memory_infos, rest) = remaining_accounts.split_at(asset_ids.len());
+    for (asset_id, info) in asset_ids.iter().zip(memory_infos) {
+        let (address, _) =
+            Pubkey::find_program_address(&[MEMORY_SEED, asset_id.as_ref()], program_id);
+        require!(info.key() == address, MemoryAssetError::MemoryAssetNotFound);
+        Account::<MemoryAccount>::try_from(info)?.require_active()?;
+    }
+
+    Ok(rest)
+}
+
+fn pay_in_lamports<'info>(
+    accounts: &PurchaseAccess<'info>,
+    protocol_fee: u64,
+    proceeds: u64,
+    payouts: &[(Pubkey, u64)],
+    recipients: &[AccountInfo<'info>],
+) -> Result<u64> {
+    let buyer = accounts.buyer.to_account_info();
+    let royalties = pay_royalties(
+        payouts,
+        &buyer,
+        recipients,
+        &accounts.system_program.to_account_info(),
+    )?;
+    let proceeds = proceeds
+        .checked_sub(royalties)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    for (to, amount) in [
+        (accounts.treasury.to_account_info(), protocol_fee),
+        (accounts.owner.to_account_info(), proceeds),
+    ] {
+        if amount > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: buyer.clone(),
+                        to,
+                    },
+                ),
+                amount,
+            )?;
+        }
+    }
+    Ok(royalties)
+}
+
+fn pay_in_tokens<'info>(
+    accounts: &PurchaseAccess<'info>,
+    payment_mint: Pubkey,
+    protocol_fee: u64,
+    proceeds: u64,
+    payouts: &[(Pubkey, u64)],
+    recipients: &'info [AccountInfo<'info>],
+) -> Result<u64> {
+    let accepted_mint = accounts
+        .accepted_mint
+        .as_ref()
+        .ok_or(MemoryAssetError::MintNotAccepted)?;
+    require!(
+        accepted_mint.mint == payment_mint,
+        MemoryAssetError::MintNotAccepted
+    );
+
+    let (buyer_token_account, owner_token_account, treasury_token_account, token_program) = match (
+        &accounts.buyer_token_account,
+        &accounts.owner_token_account,
+        &accounts.treasury_token_account,
+        &accounts.token_program,
+    ) {
+        (Some(buyer), Some(owner), Some(treasury), Some(token_program)) => {
+            (buyer, owner, treasury, token_program)
+        }
+        _ => return err!(MemoryAssetError::InvalidTokenAccount),
+    };
+    require_token_account(buyer_token_account, &payment_mint, &accounts.buyer.key())?;
+    require_token_account(owner_token_account, &payment_mint, &accounts.owner.key())?;
+    require!(
+        treasury_token_account.key() == accepted_mint.treasury_token_account,
+        MemoryAssetError::InvalidTokenAccount
+    );
+
+    let buyer = accounts.buyer.to_account_info();
+    let royalties = pay_royalties_in_tokens(
+        payouts,
+        &payment_mint,
+        token_program,
+        buyer_token_account,
+        &buyer,
+        recipients,
+    )?;
+    let proceeds = proceeds
+        .checked_sub(royalties)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    transfer_tokens(token_program, buyer_token_account, treasury_token_account, &buyer, protocol_fee)?;
+    transfer_tokens(token_program, buyer_token_account, owner_token_account, &buyer, proceeds)?;
+    Ok(royalties)
+}
+
+#[event]
+pub struct AccessPurchasedEvent {
+    pub owner: Pubkey,
+    pub buyer: Pubkey,
+    pub offer: Pubkey,
+    pub price: u64,
+    pub payment_mint: Option<Pubkey>,
+    pub protocol_fee: u64,
+    pub royalties: u64,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::permissions::Permissions;
+use super::update_access_policy::resolve_access;
+
+#[derive(Accounts)]
+pub struct RecordAccess<'info> {
+    #[account(
+        mut,
+        seeds = [ACCESS_POLICY_SEED, memory_account.owner.as_ref()],
+        bump = AccessPolicyAccount::current_bump(&access_policy)?
+    )]
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    #[account(
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    /// Key accessing the memory
+    pub accessor: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RecordAccess>, permissions: u8) -> Result<()> {
+    let policy_info = ctx.accounts.access_policy.to_account_info();
+    let memory_account = &ctx.accounts.memory_account;
+    let accessor = ctx.accounts.accessor.key();
+    let clock = Clock::get()?;
+
+    // Redacted, burned, expired or frozen memories cannot be used
+    memory_account.require_active()?;
+
+    let required = Permissions::validate(permissions)?;
+    require!(!required.is_empty(), MemoryAssetError::InvalidPermissions);
+
+    // Same precedence as `check_access`; the grant that allows the access,
+    // and every grant it was delegated through, pays for it out of its
+    // access limit and period quota
+    let grant_id = {
+        let mut data = policy_info.try_borrow_mut_data()?;
+        let (access_policy, slots) = AccessPolicyAccount::unpack_mut(&mut data);
+        let grant_id = resolve_access(access_policy, slots, memory_account, &accessor, required)?;
+        if let Some(grant_id) = grant_id {
+            let index = access_policy
+                .grants(slots)
+                .iter()
+                .position(|slot| slot.grant_id == grant_id)
+                .ok_or(MemoryAssetError::AccessGrantNotFound)?;
+            let mut charged = access_policy.ancestors(slots, &slots[index]);
+            charged.push(index);
+            for index in charged {
+                slots[index].record_access(clock.unix_timestamp);
+            }
+            access_policy.updated_at = clock.unix_timestamp;
+        }
+        grant_id
+    };
+
+    msg!("Access recorded");
+    msg!("Asset ID: {}", memory_account.asset_id);
+    msg!("Accessor: {}", accessor);
+    if let Some(grant_id) = grant_id {
+        msg!("Grant ID: {}", grant_id);
+    }
+
+    emit!(AccessRecordedEvent {
+        owner: memory_account.owner,
+        asset_id: memory_account.asset_id,
+        accessor,
+        permissions,
+        grant_id,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AccessRecordedEvent {
+    pub owner: Pubkey,
+    pub asset_id: Pubkey,
+    pub accessor: Pubkey,
+    pub permissions: u8,
+    pub grant_id: Option<u32>,
+    pub timestamp: i64,
+}
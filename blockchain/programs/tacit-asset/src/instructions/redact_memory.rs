@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct RedactMemory<'info> {
+    #[account(
+        mut,
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    /// Owner's policy, whose grants scoped to this asset are wiped
+    #[account(
+        mut,
+        seeds = [ACCESS_POLICY_SEED, memory_account.owner.as_ref()],
+        bump = AccessPolicyAccount::current_bump(&access_policy)?
+    )]
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// Open listing of the memory, closed by the redaction; required while listed
+    #[account(
+        mut,
+        seeds = [LISTING_SEED, memory_account.asset_id.as_ref()],
+        bump = listing.bump
+    )]
+    pub listing: Option<Account<'info, Listing>>,
+
+    /// CHECK: Seller receiving the closed listing's rent, validated against the listing
+    #[account(mut)]
+    pub seller: Option<UncheckedAccount<'info>>,
+
+    /// Memory owner or compliance authority
+    pub authority: Signer<'info>,
+    // remaining_accounts: writable MemoryVersionAccounts of this memory to wipe
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, RedactMemory<'info>>) -> Result<()> {
+    let memory_account = &mut ctx.accounts.memory_account;
+    let authority = ctx.accounts.authority.key();
+    let clock = Clock::get()?;
+
+    require!(
+        authority == memory_account.owner
+            || ctx.accounts.config.is_compliance_authority(&authority),
+        MemoryAssetError::Unauthorized
+    );
+
+    // A legal hold takes precedence over an erasure request, and burned,
+    // expired or already redacted memories have nothing left to erase
+    require!(!memory_account.is_frozen(), MemoryAssetError::MemoryFrozen);
+    require!(
+        memory_account.status == MemoryStatus::Active,
+        MemoryAssetError::MemoryNotActive
+    );
+
+    // Redacted content cannot stay for sale
+    let listing_closed = memory_account.is_listed();
+    if listing_closed {
+        let (listing, seller) = match (&ctx.accounts.listing, &ctx.accounts.seller) {
+            (Some(listing), Some(seller)) => (listing, seller),
+            _ => return err!(MemoryAssetError::MemoryListed),
+        };
+        require!(
+            seller.key() == listing.seller,
+            MemoryAssetError::InvalidOwner
+        );
+        listing.close(seller.to_account_info())?;
+        memory_account.delegate = None;
+    }
+
+    // Arweave content is permanent, so erasure means dropping every pointer to
+    // it. No decryption keys are stored on-chain; revoking them is left to
+    // the key service, on the event below. Long histories can be wiped over
+    // several calls.
+    let mut versions = Vec::with_capacity(ctx.remaining_accounts.len());
+    for info in ctx.remaining_accounts.iter() {
+        require!(info.is_writable, MemoryAssetError::InvalidVersionAccount);
+
+        let mut version_account = Account::<MemoryVersionAccount>::try_from(info)?;
+        require!(
+            version_account.asset_id == memory_account.asset_id,
+            MemoryAssetError::InvalidVersionAccount
+        );
+        if version_account.is_redacted() {
+            continue;
+        }
+
+        version_account.locators.clear();
+        version_account.redacted_at = Some(clock.unix_timestamp);
+        version_account.updated_at = clock.unix_timestamp;
+        version_account.exit(&crate::ID)?;
+
+        versions.push(version_account.version);
+    }
+
+    // Grants naming this asset would otherwise outlive it
+    let asset_target = memory_account.asset_id.to_bytes();
+    let grants_removed = {
+        let policy_info = ctx.accounts.access_policy.to_account_info();
+        let mut data = policy_info.try_borrow_mut_data()?;
+        let (access_policy, slots) = AccessPolicyAccount::unpack_mut(&mut data);
+        let removed = access_policy.remove_grants(slots, |slot| {
+            slot.scope_kind == GrantSlot::SCOPE_ASSET && slot.scope_target == asset_target
+        }) + access_policy.prune_orphans(slots);
+        if removed > 0 {
+            access_policy.updated_at = clock.unix_timestamp;
+        }
+        removed as u32
+    };
+
+    memory_account.status = MemoryStatus::Redacted;
+    memory_account.updated_at = clock.unix_timestamp;
+
+    msg!("Memory redacted");
+    msg!("Asset ID: {}", memory_account.asset_id);
+    msg!("Redacted by: {}", authority);
+    msg!("Versions wiped: {:?}", versions);
+    msg!("Grants removed: {}", grants_removed);
+    msg!("Listing closed: {}", listing_closed);
+
+    // Indexers, gateways and the SDK stop serving the content on this event
+    emit!(RedactedEvent {
+        owner: memory_account.owner,
+        asset_id: memory_account.asset_id,
+        authority,
+        versions,
+        grants_removed,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct RedactedEvent {
+    pub owner: Pubkey,
+    pub asset_id: Pubkey,
+    pub authority: Pubkey,
+    pub versions: Vec<u32>,
+    pub grants_removed: u32,
+    pub timestamp: i64,
+}
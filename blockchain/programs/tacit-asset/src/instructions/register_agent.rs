@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
+
+#[derive(Accounts)]
+pub struct RegisterAgent<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = AgentAccount::LEN,
+        seeds = [AGENT_SEED, owner.key().as_ref(), agent.key().as_ref()],
+        bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    #[account(
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Agent key; co-signs to prove the owner controls it
+    pub agent: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<RegisterAgent>) -> Result<()> {
+    let agent = ctx.accounts.agent.key();
+    let agent_account = &mut ctx.accounts.agent_account;
+    let clock = Clock::get()?;
+
+    agent_account.schema_version = AgentAccount::SCHEMA_VERSION;
+    agent_account.owner = ctx.accounts.owner.key();
+    agent_account.agent = agent;
+    agent_account.registered_at = clock.unix_timestamp;
+    agent_account.bump = ctx.bumps.agent_account;
+
+    msg!("Agent registered");
+    msg!("Owner: {}", agent_account.owner);
+    msg!("Agent: {}", agent);
+
+    Ok(())
+}
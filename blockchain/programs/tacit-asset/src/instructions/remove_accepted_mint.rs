@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct RemoveAcceptedMint<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ MemoryAssetError::Unauthorized
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// Offers priced in this mint can no longer be bought
+    #[account(
+        mut,
+        close = admin,
+        seeds = [ACCEPTED_MINT_SEED, accepted_mint.mint.as_ref()],
+        bump = accepted_mint.bump
+    )]
+    pub accepted_mint: Account<'info, AcceptedMint>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RemoveAcceptedMint>) -> Result<()> {
+    msg!("Payment mint removed");
+    msg!("Mint: {}", ctx.accounts.accepted_mint.mint);
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
+use crate::permissions::Permissions;
+
+#[derive(Accounts)]
+pub struct RequestAccess<'info> {
+    #[account(
+        init,
+        payer = requester,
+        space = AccessRequest::LEN,
+        seeds = [ACCESS_REQUEST_SEED, owner.key().as_ref(), requester.key().as_ref()],
+        bump
+    )]
+    pub access_request: Account<'info, AccessRequest>,
+
+    /// Requests can only be made to registered users
+    #[account(
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    /// CHECK: Owner being asked, validated by `has_one`
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub requester: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<RequestAccess>,
+    scope: GrantScope,
+    permissions: u8,
+    message_hash: [u8; 32],
+) -> Result<()> {
+    let access_request = &mut ctx.accounts.access_request;
+    let owner = ctx.accounts.owner.key();
+    let requester = ctx.accounts.requester.key();
+    let clock = Clock::get()?;
+
+    // Policy administration is never handed out on request
+    let requested = Permissions::validate(permissions)?;
+    require!(
+        !requested.is_empty() && !requested.contains(Permissions::ADMIN),
+        MemoryAssetError::InvalidPermissions
+    );
+    require!(requester != owner, MemoryAssetError::InvalidAccessRequest);
+    scope.validate()?;
+
+    access_request.schema_version = AccessRequest::SCHEMA_VERSION;
+    access_request.owner = owner;
+    access_request.requester = requester;
+    access_request.scope = scope;
+    access_request.permissions = permissions;
+    access_request.message_hash = message_hash;
+    access_request.requested_at = clock.unix_timestamp;
+    access_request.bump = ctx.bumps.access_request;
+
+    msg!("Access requested");
+    msg!("Owner: {}", owner);
+    msg!("Requester: {}", requester);
+    msg!("Permissions: {:?}", requested);
+
+    // Owners' inboxes are built from this event and the open request PDAs
+    emit!(AccessRequestedEvent {
+        owner,
+        requester,
+        permissions,
+        message_hash,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AccessRequestedEvent {
+    pub owner: Pubkey,
+    pub requester: Pubkey,
+    pub permissions: u8,
+    pub message_hash: [u8; 32],
+    pub timestamp: i64,
+}
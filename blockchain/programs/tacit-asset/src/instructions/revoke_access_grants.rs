@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
+use crate::permissions::Permissions;
+
+#[derive(Accounts)]
+pub struct RevokeAccessGrants<'info> {
+    #[account(
+        mut,
+        seeds = [ACCESS_POLICY_SEED, owner.key().as_ref()],
+        bump = AccessPolicyAccount::current_bump(&access_policy)?,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    #[account(
+        mut,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    /// CHECK: Policy owner, validated by `has_one`
+    pub owner: UncheckedAccount<'info>,
+
+    /// Policy owner, a grantee holding `Permissions::ADMIN`, or the delegator
+    /// of every grant being revoked
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RevokeAccessGrants>, revocations: Vec<GrantRevocation>) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+    let policy_info = ctx.accounts.access_policy.to_account_info();
+    let owner = ctx.accounts.owner.key();
+    let authority = ctx.accounts.authority.key();
+    let clock = Clock::get()?;
+
+    let mut data = policy_info.try_borrow_mut_data()?;
+    let (access_policy, slots) = AccessPolicyAccount::unpack_mut(&mut data);
+    let is_admin = access_policy.is_policy_admin(slots, &authority, clock.unix_timestamp);
+
+    let mut removed = 0;
+    for revocation in &revocations {
+        // Delegators may take back what they handed out, nothing more
+        if !is_admin {
+            require!(
+                access_policy.grants(slots).iter().all(|slot| {
+                    !revocation.covers(slot)
+                        || access_policy.is_delegated_by(slots, &authority, slot)
+                }),
+                MemoryAssetError::Unauthorized
+            );
+        }
+
+        // Only the owner may remove grants carrying policy administration
+        if authority != owner {
+            require!(
+                !access_policy.grants(slots).iter().any(|slot| {
+                    revocation.covers(slot) && slot.permissions().contains(Permissions::ADMIN)
+                }),
+                MemoryAssetError::Unauthorized
+            );
+        }
+
+        let count = access_policy.remove_grants(slots, |slot| revocation.covers(slot));
+        require!(count > 0, MemoryAssetError::AccessGrantNotFound);
+        removed += count;
+    }
+    // Delegations made from revoked grants go with them
+    removed += access_policy.prune_orphans(slots);
+    access_policy.updated_at = clock.unix_timestamp;
+
+    // Increment policy version
+    user_account.access_policy_version = user_account
+        .access_policy_version
+        .checked_add(1)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    msg!("Access grants revoked");
+    msg!("Owner: {}", owner);
+    msg!("Revoked by: {}", authority);
+    msg!("Grants revoked: {}", removed);
+    msg!("Total grants: {}", access_policy.grant_count);
+    msg!("Policy version: {}", user_account.access_policy_version);
+
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct RevokeAgent<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [AGENT_SEED, owner.key().as_ref(), agent_account.agent.as_ref()],
+        bump = agent_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RevokeAgent>) -> Result<()> {
+    msg!("Agent revoked");
+    msg!("Owner: {}", ctx.accounts.owner.key());
+    msg!("Agent: {}", ctx.accounts.agent_account.agent);
+
+    Ok(())
+}
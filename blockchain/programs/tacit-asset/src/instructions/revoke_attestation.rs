@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct RevokeAttestation<'info> {
+    #[account(
+        mut,
+        close = attester,
+        seeds = [ATTESTATION_SEED, attestation.asset_id.as_ref(), attester.key().as_ref()],
+        bump = attestation.bump,
+        has_one = attester @ MemoryAssetError::Unauthorized
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(mut)]
+    pub attester: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<RevokeAttestation>) -> Result<()> {
+    let attestation = &ctx.accounts.attestation;
+
+    msg!("Attestation revoked");
+    msg!("Asset ID: {}", attestation.asset_id);
+    msg!("Attester: {}", attestation.attester);
+
+    // The account is closed by the `close` constraint, so indexers rely on
+    // this event to drop the attestation from trust scores
+    emit!(AttestationRevokedEvent {
+        asset_id: attestation.asset_id,
+        attester: attestation.attester,
+        claim_type: attestation.claim_type,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AttestationRevokedEvent {
+    pub asset_id: Pubkey,
+    pub attester: Pubkey,
+    pub claim_type: ClaimType,
+    pub timestamp: i64,
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct SetRoyalties<'info> {
+    #[account(
+        mut,
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump,
+        has_one = creator @ MemoryAssetError::Unauthorized
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    /// Creator of the memory
+    pub creator: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetRoyalties>, royalties: Vec<RoyaltyShare>) -> Result<()> {
+    let memory_account = &mut ctx.accounts.memory_account;
+    let clock = Clock::get()?;
+
+    RoyaltyShare::validate_all(&royalties)?;
+    require!(
+        memory_account.status == MemoryStatus::Active,
+        MemoryAssetError::MemoryNotActive
+    );
+    // Buyers paid under the current terms, so they cannot change afterwards
+    require!(
+        !memory_account.royalties_locked,
+        MemoryAssetError::RoyaltiesLocked
+    );
+    // Terms are fixed once the memory leaves the creator, and a listing is
+    // priced against the royalties it was listed with
+    require!(
+        memory_account.owner == memory_account.creator,
+        MemoryAssetError::RoyaltiesLocked
+    );
+    require!(!memory_account.is_listed(), MemoryAssetError::MemoryListed);
+
+    memory_account.royalties = royalties;
+    memory_account.updated_at = clock.unix_timestamp;
+
+    let total_bps: u16 = memory_account.royalties.iter().map(|share| share.bps).sum();
+
+    msg!("Royalties updated");
+    msg!("Asset ID: {}", memory_account.asset_id);
+    msg!("Recipients: {}", memory_account.royalties.len());
+    msg!("Total bps: {}", total_bps);
+
+    emit!(RoyaltiesUpdatedEvent {
+        asset_id: memory_account.asset_id,
+        creator: memory_account.creator,
+        royalties: memory_account.royalties.clone(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct RoyaltiesUpdatedEvent {
+    pub asset_id: Pubkey,
+    pub creator: Pubkey,
+    pub royalties: Vec<RoyaltyShare>,
+    pub timestamp: i64,
+}
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct SetStorageMirrors<'info> {
+    #[account(
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            MEMORY_VERSION_SEED,
+            memory_account.asset_id.as_ref(),
+            &version_account.version.to_le_bytes(),
+        ],
+        bump = version_account.bump
+    )]
+    pub version_account: Account<'info, MemoryVersionAccount>,
+
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetStorageMirrors>, mirrors: Vec<StorageLocator>) -> Result<()> {
+    let version_account = &mut ctx.accounts.version_account;
+    let clock = Clock::get()?;
+
+    // Frozen or burned memories keep their locators as they are
+    ctx.accounts.memory_account.require_active()?;
+
+    // Redacted content must never be pointed at again
+    require!(!version_account.is_redacted(), MemoryAssetError::ContentRedacted);
+
+    // Validate number of locators (primary plus mirrors)
+    require!(
+        mirrors.len() < MAX_STORAGE_LOCATORS,
+        MemoryAssetError::MaxStorageLocatorsExceeded
+    );
+
+    let primary = version_account
+        .primary()
+        .cloned()
+        .ok_or(MemoryAssetError::InvalidVersion)?;
+
+    // Validate each mirror and reject duplicates
+    for (i, mirror) in mirrors.iter().enumerate() {
+        mirror.validate()?;
+        require!(
+            *mirror != primary && !mirrors[..i].contains(mirror),
+            MemoryAssetError::DuplicateStorageLocator
+        );
+    }
+
+    // Mirrors replace the existing set; the primary and version are unchanged
+    let mut locators = Vec::with_capacity(mirrors.len() + 1);
+    locators.push(primary);
+    locators.extend(mirrors.iter().cloned());
+    version_account.locators = locators;
+    version_account.updated_at = clock.unix_timestamp;
+
+    msg!("Storage mirrors updated");
+    msg!("Asset ID: {}", version_account.asset_id);
+    msg!("Version: {}", version_account.version);
+    msg!("Number of mirrors: {}", mirrors.len());
+
+    emit!(StorageMirrorsUpdatedEvent {
+        asset_id: version_account.asset_id,
+        version: version_account.version,
+        content_hash: version_account.content_hash,
+        mirrors,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct StorageMirrorsUpdatedEvent {
+    pub asset_id: Pubkey,
+    pub version: u32,
+    pub content_hash: [u8; 32],
+    pub mirrors: Vec<StorageLocator>,
+    pub timestamp: i64,
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
+
+#[derive(Accounts)]
+pub struct SetStorageQuota<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ MemoryAssetError::Unauthorized
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [USER_ACCOUNT_SEED, user_account.owner.as_ref()],
+        bump = user_account.bump,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetStorageQuota>, storage_quota: Option<u64>) -> Result<()> {
+    let user_account = &mut ctx.accounts.user_account;
+
+    user_account.storage_quota = storage_quota;
+
+    msg!("Storage quota updated");
+    msg!("User: {}", user_account.owner);
+    msg!("Quota: {:?}", storage_quota);
+    msg!("Used: {}", user_account.total_storage_bytes);
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::CurrentUserAccount;
+use super::apply_policy_template::apply_template;
+
+#[derive(Accounts)]
+pub struct SyncPolicyTemplate<'info> {
+    pub template: Account<'info, PolicyTemplate>,
+
+    #[account(
+        mut,
+        seeds = [
+            TEMPLATE_BINDING_SEED,
+            owner.key().as_ref(),
+            template.key().as_ref(),
+            binding.asset_id.unwrap_or_default().as_ref()
+        ],
+        bump = binding.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        has_one = template
+    )]
+    pub binding: Account<'info, TemplateBinding>,
+
+    #[account(
+        mut,
+        seeds = [ACCESS_POLICY_SEED, owner.key().as_ref()],
+        bump = AccessPolicyAccount::current_bump(&access_policy)?,
+        has_one = owner @ MemoryAssetError::InvalidOwner
+    )]
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    #[account(
+        mut,
+        seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
+        bump = user_account.bump,
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
+    )]
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    /// CHECK: Policy owner, validated by `has_one`
+    pub owner: UncheckedAccount<'info>,
+
+    /// Owner, or anyone when the binding propagates; pays rent when the
+    /// policy needs more grant slots
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<SyncPolicyTemplate>) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    let payer = ctx.accounts.payer.key();
+    let clock = Clock::get()?;
+
+    let binding = &mut ctx.accounts.binding;
+    require!(
+        binding.propagate || payer == owner,
+        MemoryAssetError::Unauthorized
+    );
+    // A locked-down policy stays empty until the owner restores it
+    require!(
+        !ctx.accounts.access_policy.load()?.is_locked_down(),
+        MemoryAssetError::PolicyLockedDown
+    );
+    require!(
+        ctx.accounts.template.version > binding.applied_version,
+        MemoryAssetError::TemplateUpToDate
+    );
+
+    let grants_applied = apply_template(
+        &ctx.accounts.access_policy.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        binding,
+        &ctx.accounts.template,
+        // The default policy only follows the template if the owner opted in
+        binding.sync_default_policy,
+        clock.unix_timestamp,
+    )?;
+
+    // Increment policy version
+    let user_account = &mut ctx.accounts.user_account;
+    user_account.access_policy_version = user_account
+        .access_policy_version
+        .checked_add(1)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+    msg!("Policy template synced");
+    msg!("Owner: {}", owner);
+    msg!("Template: {}", binding.template);
+    msg!("Template version: {}", binding.applied_version);
+    msg!("Grants applied: {}", grants_applied);
+    msg!("Synced by: {}", payer);
+
+    Ok(())
+}
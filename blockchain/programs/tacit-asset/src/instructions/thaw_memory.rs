@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+
+#[derive(Accounts)]
+pub struct ThawMemory<'info> {
+    #[account(
+        mut,
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// Authority that froze the memory or compliance authority
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ThawMemory>) -> Result<()> {
+    let memory_account = &mut ctx.accounts.memory_account;
+    let authority = ctx.accounts.authority.key();
+
+    let frozen_by = memory_account
+        .frozen_by
+        .ok_or(MemoryAssetError::MemoryNotFrozen)?;
+
+    // Only the freezer can thaw, except the compliance authority who can
+    // always lift a freeze
+    require!(
+        authority == frozen_by || ctx.accounts.config.is_compliance_authority(&authority),
+        MemoryAssetError::Unauthorized
+    );
+
+    let clock = Clock::get()?;
+    memory_account.frozen_by = None;
+    memory_account.updated_at = clock.unix_timestamp;
+
+    msg!("Memory thawed");
+    msg!("Asset ID: {}", memory_account.asset_id);
+    msg!("Thawed by: {}", authority);
+
+    emit!(MemoryThawedEvent {
+        asset_id: memory_account.asset_id,
+        authority,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MemoryThawedEvent {
+    pub asset_id: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
@@ -1,9 +1,12 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 use mpl_bubblegum::program::Bubblegum;
 use spl_account_compression::{program::SplAccountCompression, Noop};
 use crate::state::*;
 use crate::constants::*;
 use crate::errors::MemoryAssetError;
+use crate::fees::pay_royalties;
+use crate::migration::CurrentUserAccount;
 
 #[derive(Accounts)]
 pub struct TransferMemory<'info> {
@@ -11,16 +14,25 @@ pub struct TransferMemory<'info> {
         mut,
         seeds = [USER_ACCOUNT_SEED, current_owner.key().as_ref()],
         bump = user_account.bump,
-        has_one = owner @ MemoryAssetError::InvalidOwner
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
     )]
-    pub user_account: Account<'info, UserAccount>,
+    pub user_account: Account<'info, CurrentUserAccount>,
 
     #[account(
         seeds = [ACCESS_POLICY_SEED, current_owner.key().as_ref()],
-        bump = access_policy.bump,
+        bump = AccessPolicyAccount::current_bump(&access_policy)?,
         has_one = owner @ MemoryAssetError::InvalidOwner
     )]
-    pub access_policy: Account<'info, AccessPolicyAccount>,
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
+
+    #[account(
+        mut,
+        seeds = [MEMORY_SEED, memory_account.asset_id.as_ref()],
+        bump = memory_account.bump,
+        constraint = memory_account.owner == current_owner.key() @ MemoryAssetError::InvalidOwner
+    )]
+    pub memory_account: Account<'info, MemoryAccount>,
 
     /// Current owner of the memory asset
     #[account(mut)]
@@ -29,7 +41,8 @@ pub struct TransferMemory<'info> {
     /// Alias for current_owner (for has_one constraint)
     pub owner: Signer<'info>,
 
-    /// CHECK: New owner public key
+    /// CHECK: New owner; must sign and pays the consideration when one is declared
+    #[account(mut)]
     pub new_owner: UncheckedAccount<'info>,
 
     /// CHECK: Merkle tree account
@@ -48,14 +61,17 @@ pub struct TransferMemory<'info> {
     pub compression_program: Program<'info, SplAccountCompression>,
     pub bubblegum_program: Program<'info, Bubblegum>,
     pub system_program: Program<'info, System>,
+    // remaining_accounts: writable recipient of each royalty share, in order
 }
 
-pub fn handler(
-    ctx: Context<TransferMemory>,
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, TransferMemory<'info>>,
     new_owner: Pubkey,
+    consideration: u64,
 ) -> Result<()> {
     let current_owner = &ctx.accounts.current_owner;
-    let access_policy = &ctx.accounts.access_policy;
+    let access_policy = ctx.accounts.access_policy.load()?;
+    let memory_account = &mut ctx.accounts.memory_account;
 
     // Verify current owner has transfer permission
     // (Owner always has permission, but we check for consistency)
@@ -69,10 +85,57 @@ pub fn handler(
         new_owner != current_owner.key(),
         MemoryAssetError::InvalidOwner
     );
+    require!(
+        ctx.accounts.new_owner.key() == new_owner,
+        MemoryAssetError::InvalidOwner
+    );
+
+    // Frozen or burned memories cannot change hands
+    memory_account.require_mutable()?;
+
+    // A transfer for value pays the creator royalties out of the declared
+    // consideration, which the new owner pays and co-signs for. Only gifts
+    // (zero consideration) skip royalties; listed sales go through buy_memory
+    let mut royalties: u64 = 0;
+    if consideration > 0 {
+        let new_owner_info = ctx.accounts.new_owner.to_account_info();
+        require!(new_owner_info.is_signer, MemoryAssetError::Unauthorized);
+
+        let payouts = memory_account.royalty_payouts(consideration)?;
+        royalties = pay_royalties(
+            &payouts,
+            &new_owner_info,
+            ctx.remaining_accounts,
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        let proceeds = consideration
+            .checked_sub(royalties)
+            .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+        if proceeds > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: new_owner_info,
+                        to: current_owner.to_account_info(),
+                    },
+                ),
+                proceeds,
+            )?;
+        }
+        memory_account.royalties_locked = true;
+    }
+
+    let clock = Clock::get()?;
+    memory_account.owner = new_owner;
+    memory_account.updated_at = clock.unix_timestamp;
 
     msg!("Transferring memory asset");
+    msg!("Asset ID: {}", memory_account.asset_id);
     msg!("From: {}", current_owner.key());
     msg!("To: {}", new_owner);
+    msg!("Consideration: {}", consideration);
+    msg!("Royalties: {}", royalties);
 
     // Note: Actual Bubblegum transfer CPI call would be here
     // In production, you would call:
@@ -83,9 +146,12 @@ pub fn handler(
 
     // Emit transfer event
     emit!(TransferEvent {
+        asset_id: memory_account.asset_id,
         from: current_owner.key(),
         to: new_owner,
-        timestamp: Clock::get()?.unix_timestamp,
+        consideration,
+        royalties,
+        timestamp: clock.unix_timestamp,
     });
 
     Ok(())
@@ -93,7 +159,10 @@ pub fn handler(
 
 #[event]
 pub struct TransferEvent {
+    pub asset_id: Pubkey,
     pub from: Pubkey,
     pub to: Pubkey,
+    pub consideration: u64,
+    pub royalties: u64,
     pub timestamp: i64,
 }
@@ -1,71 +1,126 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
 use crate::state::*;
 use crate::constants::*;
 use crate::errors::MemoryAssetError;
+use crate::migration::{resize_account, CurrentUserAccount};
+use crate::permissions::Permissions;
 
 #[derive(Accounts)]
 pub struct UpdateAccessPolicy<'info> {
     #[account(
         mut,
         seeds = [ACCESS_POLICY_SEED, owner.key().as_ref()],
-        bump = access_policy.bump,
+        bump = AccessPolicyAccount::current_bump(&access_policy)?,
         has_one = owner @ MemoryAssetError::InvalidOwner
     )]
-    pub access_policy: Account<'info, AccessPolicyAccount>,
+    pub access_policy: AccountLoader<'info, AccessPolicyAccount>,
 
     #[account(
         mut,
         seeds = [USER_ACCOUNT_SEED, owner.key().as_ref()],
         bump = user_account.bump,
-        has_one = owner @ MemoryAssetError::InvalidOwner
+        has_one = owner @ MemoryAssetError::InvalidOwner,
+        constraint = user_account.is_current() @ MemoryAssetError::AccountNotMigrated
     )]
-    pub user_account: Account<'info, UserAccount>,
+    pub user_account: Account<'info, CurrentUserAccount>,
+
+    /// CHECK: Policy owner, validated by `has_one`
+    pub owner: UncheckedAccount<'info>,
 
+    /// Policy owner or a grantee holding `Permissions::ADMIN`; pays rent when
+    /// the policy needs more grant slots
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 pub fn handler(
     ctx: Context<UpdateAccessPolicy>,
     grants: Vec<AccessGrant>,
     default_policy: PolicyType,
+    replace: bool,
 ) -> Result<()> {
-    let access_policy = &mut ctx.accounts.access_policy;
     let user_account = &mut ctx.accounts.user_account;
+    let policy_info = ctx.accounts.access_policy.to_account_info();
+    let owner = ctx.accounts.owner.key();
+    let authority = ctx.accounts.authority.key();
     let clock = Clock::get()?;
 
-    // Validate number of grants
-    require!(
-        grants.len() <= MAX_ACCESS_GRANTS,
-        MemoryAssetError::MaxAccessGrantsExceeded
-    );
+    require_policy_admin(&policy_info, &authority, clock.unix_timestamp)?;
 
     // Validate each grant
     for grant in &grants {
-        // Validate permissions bitmap (only bits 0-2 should be set)
+        let permissions = grant.validate(&owner, clock.unix_timestamp)?;
+
+        // Only the owner may hand out or withhold policy administration
         require!(
-            grant.permissions & 0b11111000 == 0,
-            MemoryAssetError::InvalidPermissions
+            authority == owner || !permissions.contains(Permissions::ADMIN),
+            MemoryAssetError::Unauthorized
         );
+    }
 
-        // Validate expiration timestamp if present
-        if let Some(expires_at) = grant.expires_at {
-            require!(
-                expires_at > clock.unix_timestamp,
-                MemoryAssetError::InvalidTimestamp
-            );
-        }
+    // Each scope target occupies its own slot
+    let new_slots: Vec<GrantSlot> = grants.iter().flat_map(AccessGrant::to_slots).collect();
 
-        // Validate that grantee is not the owner
+    // Admins cannot overwrite another admin's grant either
+    if authority != owner {
+        let data = policy_info.try_borrow_data()?;
+        let (access_policy, slots) = AccessPolicyAccount::unpack(&data);
         require!(
-            grant.grantee != ctx.accounts.owner.key(),
-            MemoryAssetError::InvalidOwner
+            new_slots.iter().all(|slot| {
+                access_policy.find_grant(slots, slot).map_or(true, |index| {
+                    !slots[index].permissions().contains(Permissions::ADMIN)
+                })
+            }),
+            MemoryAssetError::Unauthorized
         );
     }
 
+    reserve_grant_slots(
+        &policy_info,
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &new_slots,
+    )?;
+
     // Update access policy
-    access_policy.grants = grants.clone();
-    access_policy.default_policy = default_policy;
+    let mut data = policy_info.try_borrow_mut_data()?;
+    let (access_policy, slots) = AccessPolicyAccount::unpack_mut(&mut data);
+    access_policy.capacity = slots.len() as u32;
+
+    // Replacing drops the top-level grants left out of `grants`, purchased
+    // ones included and without refund, like revoking them would. Template
+    // slots belong to their binding and delegations follow their parent
+    let grants_removed = if replace {
+        let is_dropped = |slot: &GrantSlot| {
+            slot.depth == 0
+                && slot.origin != GrantSlot::ORIGIN_TEMPLATE
+                && !new_slots.iter().any(|new_slot| new_slot.key() == slot.key())
+        };
+        require!(
+            authority == owner
+                || !access_policy.grants(slots).iter().any(|slot| {
+                    is_dropped(slot) && slot.permissions().contains(Permissions::ADMIN)
+                }),
+            MemoryAssetError::Unauthorized
+        );
+        access_policy.remove_grants(slots, is_dropped)
+    } else {
+        0
+    };
+
+    for slot in new_slots {
+        access_policy.upsert_grant(slots, slot)?;
+    }
+    // Delegations hang off grants that may just have lost `SHARE`
+    let orphans_removed = access_policy.prune_orphans(slots);
+    access_policy.default_policy = default_policy as u8;
+    // Only the owner lifts a lockdown; admins may be the compromised party
+    if authority == owner {
+        access_policy.locked_down = 0;
+    }
     access_policy.updated_at = clock.unix_timestamp;
 
     // Increment policy version
@@ -75,50 +130,166 @@ pub fn handler(
         .ok_or(MemoryAssetError::ArithmeticOverflow)?;
 
     msg!("Access policy updated");
-    msg!("Owner: {}", ctx.accounts.owner.key());
-    msg!("Number of grants: {}", grants.len());
+    msg!("Owner: {}", owner);
+    msg!("Updated by: {}", authority);
+    msg!("Grants updated: {}", grants.len());
+    msg!("Grants removed: {}", grants_removed);
+    msg!("Delegations removed: {}", orphans_removed);
+    msg!("Total grants: {}", access_policy.grant_count);
     msg!("Default policy: {:?}", default_policy);
     msg!("Policy version: {}", user_account.access_policy_version);
 
     Ok(())
 }
 
-/// Helper function to check if a user has access
+/// Grow the policy in `policy_info` so `new_slots` fit, at most once per
+/// instruction
+pub fn reserve_grant_slots<'info>(
+    policy_info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_slots: &[GrantSlot],
+) -> Result<()> {
+    let (required, capacity) = {
+        let data = policy_info.try_borrow_data()?;
+        let (access_policy, slots) = AccessPolicyAccount::unpack(&data);
+        let mut inserted: Vec<_> = new_slots
+            .iter()
+            .filter(|slot| access_policy.find_grant(slots, slot).is_err())
+            .map(GrantSlot::key)
+            .collect();
+        inserted.sort();
+        inserted.dedup();
+        (
+            access_policy.grant_count as usize + inserted.len(),
+            slots.len(),
+        )
+    };
+
+    // Validate number of grants
+    require!(
+        required <= MAX_ACCESS_GRANTS,
+        MemoryAssetError::MaxAccessGrantsExceeded
+    );
+
+    if required > capacity {
+        // Account data may grow by at most MAX_PERMITTED_DATA_INCREASE per
+        // instruction, so larger batches have to be split across calls
+        let max_growth = MAX_PERMITTED_DATA_INCREASE / GrantSlot::LEN;
+        require!(
+            required - capacity <= max_growth,
+            MemoryAssetError::GrantBatchTooLarge
+        );
+
+        let increments = (required - capacity + GRANT_CAPACITY_INCREMENT - 1)
+            / GRANT_CAPACITY_INCREMENT;
+        let new_capacity = (capacity + increments * GRANT_CAPACITY_INCREMENT)
+            .min(capacity + max_growth)
+            .min(MAX_ACCESS_GRANTS);
+        resize_account(
+            policy_info,
+            payer,
+            system_program,
+            AccessPolicyAccount::space(new_capacity),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Ensure `authority` may edit the policy stored in `policy_info`
+pub fn require_policy_admin(
+    policy_info: &AccountInfo,
+    authority: &Pubkey,
+    current_time: i64,
+) -> Result<()> {
+    let data = policy_info.try_borrow_data()?;
+    let (access_policy, slots) = AccessPolicyAccount::unpack(&data);
+    require!(
+        access_policy.is_policy_admin(slots, authority, current_time),
+        MemoryAssetError::Unauthorized
+    );
+    Ok(())
+}
+
+/// Helper function to check if a user has access to `memory`
+///
+/// Precedence, first match wins:
+/// 1. The policy owner always has access.
+/// 2. A deny grant in effect covering the memory and the permission denies
+///    access, regardless of allow grants or the default policy.
+/// 3. A valid allow grant covering the memory and the permission allows access.
+/// 4. If a covering allow grant exists but is expired, not yet valid,
+///    exhausted or over its period quota, that is reported instead of
+///    falling back to the default.
+/// 5. Otherwise the default policy decides for `READ`; every other
+///    permission needs an explicit grant.
 pub fn check_access(
     access_policy: &AccessPolicyAccount,
+    slots: &[GrantSlot],
+    memory: &MemoryAccount,
     requester: &Pubkey,
-    required_permission: u8,
+    required_permission: Permissions,
 ) -> Result<bool> {
+    resolve_access(access_policy, slots, memory, requester, required_permission)?;
+    Ok(true)
+}
+
+/// Resolve access like `check_access`, returning the ID of the allow grant
+/// that permits it, or None when ownership or the default policy does
+pub fn resolve_access(
+    access_policy: &AccessPolicyAccount,
+    slots: &[GrantSlot],
+    memory: &MemoryAccount,
+    requester: &Pubkey,
+    required_permission: Permissions,
+) -> Result<Option<u32>> {
     let clock = Clock::get()?;
     let current_time = clock.unix_timestamp;
 
     // Owner always has access
     if requester == &access_policy.owner {
-        return Ok(true);
+        return Ok(None);
     }
 
-    // Check grants
-    for grant in &access_policy.grants {
-        if grant.grantee == *requester {
-            // Check if grant is valid
-            if !grant.is_valid(current_time) {
-                if grant.is_expired(current_time) {
-                    return err!(MemoryAssetError::AccessGrantExpired);
-                } else {
-                    return err!(MemoryAssetError::AccessGrantExhausted);
-                }
-            }
-
-            // Check if grant has required permission
-            if grant.permissions & required_permission != 0 {
-                return Ok(true);
-            }
+    let grants = access_policy.grants_for(slots, requester);
+    let covering = || {
+        grants
+            .iter()
+            .filter(|grant| {
+                grant.matches(memory) && grant.permissions().contains(required_permission)
+            })
+    };
+
+    // Deny grants override everything but ownership
+    if covering().any(|grant| grant.is_deny() && grant.is_in_effect(current_time)) {
+        return err!(MemoryAssetError::AccessDenied);
+    }
+
+    // Any valid allow grant with the permission is enough. A delegated grant
+    // also draws on the accesses and quota of every grant above it
+    let mut invalid_grant = None;
+    for grant in covering().filter(|grant| !grant.is_deny()) {
+        let reason = grant.invalid_reason(current_time).or_else(|| {
+            access_policy
+                .ancestors(slots, grant)
+                .into_iter()
+                .find_map(|index| slots[index].invalid_reason(current_time))
+        });
+        match reason {
+            None => return Ok(Some(grant.grant_id)),
+            reason => invalid_grant = reason,
         }
     }
 
+    // Report why a matching grant could not be used
+    if let Some(code) = invalid_grant {
+        return Err(error!(code));
+    }
+
     // Check default policy
-    match access_policy.default_policy {
-        PolicyType::Allow => Ok(true),
+    match access_policy.policy_type() {
+        PolicyType::Allow => Ok(None),
         PolicyType::Deny => err!(MemoryAssetError::AccessDenied),
     }
 }
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::resize_account;
+
+#[derive(Accounts)]
+pub struct UpdatePolicyTemplate<'info> {
+    #[account(
+        mut,
+        seeds = [POLICY_TEMPLATE_SEED, authority.key().as_ref(), template.name.as_bytes()],
+        bump = template.bump,
+        has_one = authority @ MemoryAssetError::Unauthorized
+    )]
+    pub template: Account<'info, PolicyTemplate>,
+
+    /// Template authority; pays rent when the template grows and is refunded
+    /// when it shrinks
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<UpdatePolicyTemplate>,
+    grants: Vec<AccessGrant>,
+    default_policy: PolicyType,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    PolicyTemplate::validate_grants(&grants, clock.unix_timestamp)?;
+
+    // Size the account for the new grants before they are serialized on exit
+    resize_account(
+        &ctx.accounts.template.to_account_info(),
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        PolicyTemplate::space(&ctx.accounts.template.name, &grants),
+    )?;
+
+    let template = &mut ctx.accounts.template;
+    template.grants = grants;
+    template.default_policy = default_policy;
+    template.version = template
+        .version
+        .checked_add(1)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+    template.updated_at = clock.unix_timestamp;
+
+    msg!("Policy template updated");
+    msg!("Authority: {}", template.authority);
+    msg!("Name: {}", template.name);
+    msg!("Grants: {}", template.grants.len());
+    msg!("Version: {}", template.version);
+
+    // Bindings that opted into propagation can now be synced by anyone
+    emit!(PolicyTemplateUpdatedEvent {
+        template: template.key(),
+        authority: template.authority,
+        version: template.version,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct PolicyTemplateUpdatedEvent {
+    pub template: Pubkey,
+    pub authority: Pubkey,
+    pub version: u32,
+    pub timestamp: i64,
+}
@@ -6,6 +6,9 @@ pub mod state;
 pub mod instructions;
 pub mod errors;
 pub mod constants;
+pub mod fees;
+pub mod migration;
+pub mod permissions;
 
 use instructions::*;
 use state::*;
@@ -22,36 +25,348 @@ pub mod memory_asset {
     /// Mint a memory as a compressed NFT
     pub fn mint_memory(
         ctx: Context<MintMemory>,
-        arweave_id: String,
+        asset_id: Pubkey,
+        storage: StorageLocator,
         content_hash: [u8; 32],
+        content_size: u64,
         metadata_uri: String,
+        signed_at: Option<i64>,
+        expires_at: Option<i64>,
+        batch_id: Option<String>,
+        memory_type: Option<String>,
     ) -> Result<()> {
-        instructions::mint_memory::handler(ctx, arweave_id, content_hash, metadata_uri)
+        instructions::mint_memory::handler(
+            ctx,
+            asset_id,
+            storage,
+            content_hash,
+            content_size,
+            metadata_uri,
+            signed_at,
+            expires_at,
+            batch_id,
+            memory_type,
+        )
     }
 
-    /// Update access policy for memory assets
+    /// Add or update access grants and set the default policy; with
+    /// `replace`, owner-issued grants not in `grants` are removed
     pub fn update_access_policy(
         ctx: Context<UpdateAccessPolicy>,
         grants: Vec<AccessGrant>,
         default_policy: PolicyType,
+        replace: bool,
     ) -> Result<()> {
-        instructions::update_access_policy::handler(ctx, grants, default_policy)
+        instructions::update_access_policy::handler(ctx, grants, default_policy, replace)
     }
 
-    /// Transfer memory asset to a new owner
-    pub fn transfer_memory(
-        ctx: Context<TransferMemory>,
+    /// Transfer memory asset to a new owner, paying creator royalties out of
+    /// any declared consideration
+    pub fn transfer_memory<'info>(
+        ctx: Context<'_, '_, 'info, 'info, TransferMemory<'info>>,
         new_owner: Pubkey,
+        consideration: u64,
     ) -> Result<()> {
-        instructions::transfer_memory::handler(ctx, new_owner)
+        instructions::transfer_memory::handler(ctx, new_owner, consideration)
     }
 
     /// Create a new version of a memory asset
     pub fn create_version(
         ctx: Context<CreateVersion>,
-        arweave_id: String,
+        storage: StorageLocator,
         content_hash: [u8; 32],
+        content_size: u64,
     ) -> Result<()> {
-        instructions::create_version::handler(ctx, arweave_id, content_hash)
+        instructions::create_version::handler(ctx, storage, content_hash, content_size)
+    }
+
+    /// Attest to a memory asset as an independent verifier
+    pub fn attest_memory(
+        ctx: Context<AttestMemory>,
+        asset_id: Pubkey,
+        claim_type: ClaimType,
+        score: u8,
+        evidence_uri: Option<String>,
+    ) -> Result<()> {
+        instructions::attest_memory::handler(ctx, asset_id, claim_type, score, evidence_uri)
+    }
+
+    /// Revoke a previously issued attestation
+    pub fn revoke_attestation(ctx: Context<RevokeAttestation>) -> Result<()> {
+        instructions::revoke_attestation::handler(ctx)
+    }
+
+    /// Register an agent key allowed to sign memory content; the agent co-signs
+    pub fn register_agent(ctx: Context<RegisterAgent>) -> Result<()> {
+        instructions::register_agent::handler(ctx)
+    }
+
+    /// Revoke a registered agent key
+    pub fn revoke_agent(ctx: Context<RevokeAgent>) -> Result<()> {
+        instructions::revoke_agent::handler(ctx)
+    }
+
+    /// Replace the storage mirrors of a memory version
+    pub fn set_storage_mirrors(
+        ctx: Context<SetStorageMirrors>,
+        mirrors: Vec<StorageLocator>,
+    ) -> Result<()> {
+        instructions::set_storage_mirrors::handler(ctx, mirrors)
+    }
+
+    /// Repoint a memory version to a new storage location with identical content
+    pub fn migrate_storage(
+        ctx: Context<MigrateStorage>,
+        new_storage: StorageLocator,
+        content_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::migrate_storage::handler(ctx, new_storage, content_hash)
+    }
+
+    /// Initialize the program config (upgrade authority only)
+    pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+        instructions::initialize_config::handler(ctx, params)
+    }
+
+    /// Update the program config
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        instructions::update_config::handler(ctx, params)
+    }
+
+    /// Set a per-user storage quota override
+    pub fn set_storage_quota(
+        ctx: Context<SetStorageQuota>,
+        storage_quota: Option<u64>,
+    ) -> Result<()> {
+        instructions::set_storage_quota::handler(ctx, storage_quota)
+    }
+
+    /// Top up prepaid storage credit with lamports
+    pub fn deposit_credit(ctx: Context<DepositCredit>, amount: u64) -> Result<()> {
+        instructions::deposit_credit::handler(ctx, amount)
+    }
+
+    /// Close a user's accounts and refund remaining credit
+    pub fn close_user(ctx: Context<CloseUser>) -> Result<()> {
+        instructions::close_user::handler(ctx)
+    }
+
+    /// Upgrade a user account to the current layout, creating its prepaid credit
+    pub fn migrate_user_account(ctx: Context<MigrateUserAccount>) -> Result<()> {
+        instructions::migrate_user_account::handler(ctx)
+    }
+
+    /// Upgrade an access policy account to the current layout
+    pub fn migrate_access_policy(ctx: Context<MigrateAccessPolicy>) -> Result<()> {
+        instructions::migrate_access_policy::handler(ctx)
+    }
+
+    /// Freeze a memory so it cannot be transferred, burned or re-versioned
+    pub fn freeze_memory(ctx: Context<FreezeMemory>) -> Result<()> {
+        instructions::freeze_memory::handler(ctx)
+    }
+
+    /// Lift a freeze on a memory
+    pub fn thaw_memory(ctx: Context<ThawMemory>) -> Result<()> {
+        instructions::thaw_memory::handler(ctx)
+    }
+
+    /// Burn a memory asset
+    pub fn burn_memory(ctx: Context<BurnMemory>) -> Result<()> {
+        instructions::burn_memory::handler(ctx)
+    }
+
+    /// Tombstone a memory whose retention deadline has passed (permissionless)
+    pub fn expire_memory(ctx: Context<ExpireMemory>) -> Result<()> {
+        instructions::expire_memory::handler(ctx)
+    }
+
+    /// Redact a memory, wipe the storage locators of the given versions and
+    /// close any open listing. Decryption keys are never stored on-chain
+    pub fn redact_memory<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RedactMemory<'info>>,
+    ) -> Result<()> {
+        instructions::redact_memory::handler(ctx)
+    }
+
+    /// Remove access grants by grantee, optionally limited to one scope
+    pub fn revoke_access_grants(
+        ctx: Context<RevokeAccessGrants>,
+        revocations: Vec<GrantRevocation>,
+    ) -> Result<()> {
+        instructions::revoke_access_grants::handler(ctx, revocations)
+    }
+
+    /// Re-grant a subset of the delegator's own access to another grantee
+    pub fn delegate_access(ctx: Context<DelegateAccess>, grant: AccessGrant) -> Result<()> {
+        instructions::delegate_access::handler(ctx, grant)
+    }
+
+    /// Remove expired and exhausted grants from a policy (permissionless)
+    pub fn prune_grants(ctx: Context<PruneGrants>) -> Result<()> {
+        instructions::prune_grants::handler(ctx)
+    }
+
+    /// Revoke every grant and the given agent keys and deny by default
+    pub fn lockdown<'info>(ctx: Context<'_, '_, 'info, 'info, Lockdown<'info>>) -> Result<()> {
+        instructions::lockdown::handler(ctx)
+    }
+
+    /// Create a named, reusable set of grants and a default policy
+    pub fn create_policy_template(
+        ctx: Context<CreatePolicyTemplate>,
+        name: String,
+        grants: Vec<AccessGrant>,
+        default_policy: PolicyType,
+    ) -> Result<()> {
+        instructions::create_policy_template::handler(ctx, name, grants, default_policy)
+    }
+
+    /// Replace a template's grants and default policy
+    pub fn update_policy_template(
+        ctx: Context<UpdatePolicyTemplate>,
+        grants: Vec<AccessGrant>,
+        default_policy: PolicyType,
+    ) -> Result<()> {
+        instructions::update_policy_template::handler(ctx, grants, default_policy)
+    }
+
+    /// Apply a template to the owner's policy, or to one asset in it
+    pub fn apply_policy_template(
+        ctx: Context<ApplyPolicyTemplate>,
+        asset_id: Option<Pubkey>,
+        propagate: bool,
+        sync_default_policy: bool,
+    ) -> Result<()> {
+        instructions::apply_policy_template::handler(ctx, asset_id, propagate, sync_default_policy)
+    }
+
+    /// Re-apply an updated template (permissionless when the binding propagates)
+    pub fn sync_policy_template(ctx: Context<SyncPolicyTemplate>) -> Result<()> {
+        instructions::sync_policy_template::handler(ctx)
+    }
+
+    /// Stop tracking a template, keeping the grants it wrote
+    pub fn detach_policy_template(ctx: Context<DetachPolicyTemplate>) -> Result<()> {
+        instructions::detach_policy_template::handler(ctx)
+    }
+
+    /// Ask an owner for access to their memories
+    pub fn request_access(
+        ctx: Context<RequestAccess>,
+        scope: GrantScope,
+        permissions: u8,
+        message_hash: [u8; 32],
+    ) -> Result<()> {
+        instructions::request_access::handler(ctx, scope, permissions, message_hash)
+    }
+
+    /// Turn a pending access request into a grant and close it
+    pub fn approve_request(
+        ctx: Context<ApproveRequest>,
+        expires_at: Option<i64>,
+        max_access: Option<u32>,
+    ) -> Result<()> {
+        instructions::approve_request::handler(ctx, expires_at, max_access)
+    }
+
+    /// Close a pending access request without granting it
+    pub fn deny_request(ctx: Context<DenyRequest>) -> Result<()> {
+        instructions::deny_request::handler(ctx)
+    }
+
+    /// Withdraw one's own pending access request and reclaim its rent
+    pub fn cancel_request(ctx: Context<CancelRequest>) -> Result<()> {
+        instructions::cancel_request::handler(ctx)
+    }
+
+    /// Publish an offer selling time-limited access for lamports or tokens
+    pub fn create_access_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateAccessOffer<'info>>,
+        offer_id: u64,
+        scope: GrantScope,
+        permissions: u8,
+        duration: i64,
+        price: u64,
+        payment_mint: Option<Pubkey>,
+        max_access: Option<u32>,
+    ) -> Result<()> {
+        instructions::create_access_offer::handler(
+            ctx,
+            offer_id,
+            scope,
+            permissions,
+            duration,
+            price,
+            payment_mint,
+            max_access,
+        )
+    }
+
+    /// Withdraw an access offer
+    pub fn close_access_offer(ctx: Context<CloseAccessOffer>) -> Result<()> {
+        instructions::close_access_offer::handler(ctx)
+    }
+
+    /// Buy an offer: pay the owner and protocol and receive a matching grant
+    pub fn purchase_access<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PurchaseAccess<'info>>,
+    ) -> Result<()> {
+        instructions::purchase_access::handler(ctx)
+    }
+
+    /// Accept an SPL token for access sales and storage fees (admin only)
+    pub fn add_accepted_mint(
+        ctx: Context<AddAcceptedMint>,
+        base_storage_fee: u64,
+        fee_per_kib: u64,
+    ) -> Result<()> {
+        instructions::add_accepted_mint::handler(ctx, base_storage_fee, fee_per_kib)
+    }
+
+    /// Stop accepting an SPL token (admin only)
+    pub fn remove_accepted_mint(ctx: Context<RemoveAcceptedMint>) -> Result<()> {
+        instructions::remove_accepted_mint::handler(ctx)
+    }
+
+    /// Set the creator royalty shares paid on sales, while the creator still
+    /// owns the memory, it is not listed and nothing has been sold under them
+    pub fn set_royalties(ctx: Context<SetRoyalties>, royalties: Vec<RoyaltyShare>) -> Result<()> {
+        instructions::set_royalties::handler(ctx, royalties)
+    }
+
+    /// List a memory for sale at a fixed price until `expires_at`, optionally
+    /// to a single buyer, making the listing its leaf delegate
+    pub fn list_memory<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ListMemory<'info>>,
+        price: u64,
+        allowed_buyer: Option<Pubkey>,
+        expires_at: i64,
+        leaf: CompressedLeaf,
+    ) -> Result<()> {
+        instructions::list_memory::handler(ctx, price, allowed_buyer, expires_at, leaf)
+    }
+
+    /// Take a memory off the market, handing its leaf delegate back
+    pub fn cancel_listing<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelListing<'info>>,
+        leaf: CompressedLeaf,
+    ) -> Result<()> {
+        instructions::cancel_listing::handler(ctx, leaf)
+    }
+
+    /// Buy a listed memory: pay the seller, royalties and protocol and take
+    /// the compressed NFT and its memory account in one transaction
+    pub fn buy_memory<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyMemory<'info>>,
+        max_price: u64,
+        leaf: CompressedLeaf,
+    ) -> Result<()> {
+        instructions::buy_memory::handler(ctx, max_price, leaf)
+    }
+
+    /// Use a memory under a grant, counting against its access limit and quota
+    pub fn record_access(ctx: Context<RecordAccess>, permissions: u8) -> Result<()> {
+        instructions::record_access::handler(ctx, permissions)
     }
 }
@@ -0,0 +1,274 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use std::ops::{Deref, DerefMut};
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::fees::transfer_lamports;
+use crate::state::*;
+
+/// `UserAccount` layout before `schema_version` was introduced
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct UserAccountV0 {
+    pub owner: Pubkey,
+    pub access_policy_version: u32,
+    pub memory_count: u64,
+    pub total_storage_bytes: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl UserAccountV0 {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // owner
+        4 +  // access_policy_version
+        8 +  // memory_count
+        8 +  // total_storage_bytes
+        8 +  // created_at
+        1;   // bump
+
+    /// Upgrade to the current layout
+    pub fn upgrade(self) -> UserAccount {
+        UserAccount {
+            schema_version: UserAccount::SCHEMA_VERSION,
+            owner: self.owner,
+            access_policy_version: self.access_policy_version,
+            memory_count: self.memory_count,
+            // Legacy totals counted metadata bytes, not content
+            total_storage_bytes: 0,
+            storage_quota: None,
+            created_at: self.created_at,
+            bump: self.bump,
+        }
+    }
+}
+
+/// `AccessGrant` layout used by `AccessPolicyAccountV0` and `AccessPolicyAccountV1`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AccessGrantV0 {
+    pub grantee: Pubkey,
+    pub permissions: u8,
+    pub expires_at: Option<i64>,
+    pub max_access: Option<u32>,
+    pub current_access: u32,
+}
+
+impl AccessGrantV0 {
+    pub const LEN: usize = 32 + // grantee
+        1 +  // permissions
+        9 +  // expires_at (1 + 8)
+        5 +  // max_access (1 + 4)
+        4;   // current_access
+
+    /// Upgrade to the current layout
+    pub fn upgrade(self) -> AccessGrant {
+        AccessGrant {
+            grantee: self.grantee,
+            permissions: self.permissions,
+            expires_at: self.expires_at,
+            max_access: self.max_access,
+            current_access: self.current_access,
+            scope: GrantScope::All,
+            effect: GrantEffect::Allow,
+            not_before: None,
+            quota: None,
+        }
+    }
+}
+
+/// `AccessPolicyAccount` layout before `schema_version` was introduced
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AccessPolicyAccountV0 {
+    pub owner: Pubkey,
+    pub grants: Vec<AccessGrantV0>,
+    pub default_policy: PolicyType,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl AccessPolicyAccountV0 {
+    pub const MAX_GRANTS: usize = 10;
+    pub const LEN: usize = 8 + // discriminator
+        32 + // owner
+        4 + (AccessGrantV0::LEN * Self::MAX_GRANTS) + // grants vec
+        1 +  // default_policy
+        8 +  // updated_at
+        1;   // bump
+
+    /// Upgrade to the next layout
+    pub fn upgrade(self) -> AccessPolicyAccountV1 {
+        AccessPolicyAccountV1 {
+            schema_version: 1,
+            owner: self.owner,
+            grants: self.grants,
+            default_policy: self.default_policy,
+            updated_at: self.updated_at,
+            bump: self.bump,
+        }
+    }
+}
+
+/// Borsh `AccessPolicyAccount` layout before grants moved to zero-copy slots
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AccessPolicyAccountV1 {
+    pub schema_version: u8,
+    pub owner: Pubkey,
+    pub grants: Vec<AccessGrantV0>,
+    pub default_policy: PolicyType,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl AccessPolicyAccountV1 {
+    pub const MAX_GRANTS: usize = 10;
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // schema_version
+        32 + // owner
+        4 + (AccessGrantV0::LEN * Self::MAX_GRANTS) + // grants vec
+        1 +  // default_policy
+        8 +  // updated_at
+        1;   // bump
+
+    /// Write the policy into `data` using the zero-copy layout, grants sorted
+    /// by grantee. `data` must be sized for at least `grants.len()` slots.
+    pub fn write_current(mut self, data: &mut [u8]) {
+        // Legacy policies allowed duplicate grantees; the first one won
+        self.grants.sort_by(|a, b| a.grantee.cmp(&b.grantee));
+        self.grants.dedup_by_key(|grant| grant.grantee);
+        let mut grants: Vec<GrantSlot> = self
+            .grants
+            .into_iter()
+            .flat_map(|grant| grant.upgrade().to_slots())
+            .collect();
+        for (index, slot) in grants.iter_mut().enumerate() {
+            slot.grant_id = index as u32 + 1;
+        }
+
+        // Slots past the migrated grants may hold stale Borsh bytes
+        data.fill(0);
+        data[..8].copy_from_slice(&AccessPolicyAccount::DISCRIMINATOR);
+        let (header, slots) = AccessPolicyAccount::unpack_mut(data);
+        header.schema_version = AccessPolicyAccount::SCHEMA_VERSION;
+        header.default_policy = self.default_policy as u8;
+        header.bump = self.bump;
+        header.owner = self.owner;
+        header.grant_count = grants.len() as u32;
+        header.last_grant_id = grants.len() as u32;
+        header.capacity = slots.len() as u32;
+        header.updated_at = self.updated_at;
+
+        slots[..grants.len()].copy_from_slice(&grants);
+    }
+
+    /// Slot capacity to allocate when migrating
+    pub fn capacity(&self) -> usize {
+        self.grants.len().max(INITIAL_GRANT_CAPACITY)
+    }
+}
+
+/// `UserAccount` that reports legacy layouts as `AccountNotMigrated`.
+/// Legacy data fails Borsh decoding before an `is_current` constraint could
+/// run, so the schema is checked from the raw account size first.
+#[derive(Clone)]
+pub struct CurrentUserAccount(UserAccount);
+
+impl AccountDeserialize for CurrentUserAccount {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        if buf.len() == UserAccountV0::LEN {
+            return err!(MemoryAssetError::AccountNotMigrated);
+        }
+        UserAccount::try_deserialize(buf).map(Self)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        UserAccount::try_deserialize_unchecked(buf).map(Self)
+    }
+}
+
+impl AccountSerialize for CurrentUserAccount {
+    fn try_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<()> {
+        self.0.try_serialize(writer)
+    }
+}
+
+impl Owner for CurrentUserAccount {
+    fn owner() -> Pubkey {
+        UserAccount::owner()
+    }
+}
+
+impl Deref for CurrentUserAccount {
+    type Target = UserAccount;
+
+    fn deref(&self) -> &UserAccount {
+        &self.0
+    }
+}
+
+impl DerefMut for CurrentUserAccount {
+    fn deref_mut(&mut self) -> &mut UserAccount {
+        &mut self.0
+    }
+}
+
+/// Resize `account` to `new_len`, topping up rent from `payer` when it
+/// grows and refunding rent above the new minimum to `payer` when it shrinks
+pub fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let minimum_balance = Rent::get()?.minimum_balance(new_len);
+    if new_len < account.data_len() {
+        let excess = account.lamports().saturating_sub(minimum_balance);
+        if excess > 0 {
+            transfer_lamports(account, payer, excess)?;
+        }
+    }
+
+    let rent_due = minimum_balance.saturating_sub(account.lamports());
+    if rent_due > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+
+    account.realloc(new_len, true)?;
+
+    Ok(())
+}
+
+/// Resize `account` to `new_len`, topping up rent from `payer`, and
+/// overwrite it with `value` (discriminator included)
+pub fn rewrite_account<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+    value: &T,
+) -> Result<()> {
+    resize_account(account, payer, system_program, new_len)?;
+
+    let mut data = account.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    value.try_serialize(&mut writer)?;
+
+    Ok(())
+}
+
+#[event]
+pub struct AccountMigratedEvent {
+    pub account: Pubkey,
+    pub owner: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
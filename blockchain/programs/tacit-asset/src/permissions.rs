@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+use bitflags::bitflags;
+use crate::errors::MemoryAssetError;
+
+bitflags! {
+    /// Permissions carried by an access grant, stored on-chain as a `u8`
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Permissions: u8 {
+        /// Read memory content
+        const READ = 1 << 0;
+        /// Create new versions
+        const WRITE = 1 << 1;
+        /// Transfer the memory asset
+        const TRANSFER = 1 << 2;
+        /// Re-grant held permissions to other keys
+        const SHARE = 1 << 3;
+        /// Burn the memory asset
+        const DELETE = 1 << 4;
+        /// Edit the owner's access policy
+        const ADMIN = 1 << 5;
+    }
+}
+
+impl Permissions {
+    /// Parse a raw bitmap, rejecting unknown bits
+    pub fn validate(bits: u8) -> Result<Self> {
+        Self::from_bits(bits).ok_or_else(|| error!(MemoryAssetError::InvalidPermissions))
+    }
+}
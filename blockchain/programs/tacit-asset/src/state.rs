@@ -1,16 +1,25 @@
 use anchor_lang::prelude::*;
+use mpl_bubblegum::utils::get_asset_id;
+use crate::constants::*;
+use crate::errors::MemoryAssetError;
+use crate::migration::{AccessPolicyAccountV0, AccessPolicyAccountV1};
+use crate::permissions::Permissions;
 
 /// User account storing memory asset configuration and access policies
 #[account]
 pub struct UserAccount {
+    /// Account layout version
+    pub schema_version: u8,
     /// Owner of this account
     pub owner: Pubkey,
     /// Version of the access policy
     pub access_policy_version: u32,
     /// Total number of memories minted
     pub memory_count: u64,
-    /// Total storage bytes used
+    /// Total content bytes stored across all memory versions
     pub total_storage_bytes: u64,
+    /// Per-user storage quota override set by the admin
+    pub storage_quota: Option<u64>,
     /// Account creation timestamp
     pub created_at: i64,
     /// PDA bump seed
@@ -18,13 +27,179 @@ pub struct UserAccount {
 }
 
 impl UserAccount {
+    pub const SCHEMA_VERSION: u8 = 1;
     pub const LEN: usize = 8 + // discriminator
+        1 +  // schema_version
         32 + // owner
         4 +  // access_policy_version
         8 +  // memory_count
         8 +  // total_storage_bytes
+        9 +  // storage_quota (1 + 8)
         8 +  // created_at
         1;   // bump
+
+    /// Check the account has been migrated to the current layout
+    pub fn is_current(&self) -> bool {
+        self.schema_version == Self::SCHEMA_VERSION
+    }
+
+    /// Account for `content_size` new bytes, enforcing the effective quota
+    pub fn record_storage(&mut self, content_size: u64, config: &ProgramConfig) -> Result<()> {
+        require!(content_size > 0, MemoryAssetError::InvalidContentSize);
+
+        let total = self
+            .total_storage_bytes
+            .checked_add(content_size)
+            .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+
+        // A per-user override takes precedence over the config default
+        if let Some(quota) = self.storage_quota.or(config.default_storage_quota) {
+            require!(total <= quota, MemoryAssetError::StorageQuotaExceeded);
+        }
+
+        self.total_storage_bytes = total;
+        Ok(())
+    }
+}
+
+/// Program-wide configuration managed by the admin
+#[account]
+pub struct ProgramConfig {
+    /// Account layout version
+    pub schema_version: u8,
+    /// Admin allowed to update the config
+    pub admin: Pubkey,
+    /// Default per-user storage quota in bytes (None = unlimited)
+    pub default_storage_quota: Option<u64>,
+    /// Account receiving storage fees
+    pub treasury: Pubkey,
+    /// Flat fee in lamports charged per minted memory or version
+    pub base_storage_fee: u64,
+    /// Fee in lamports per started KiB of content
+    pub fee_per_kib: u64,
+    /// Authority allowed to freeze any memory for legal holds
+    pub compliance_authority: Option<Pubkey>,
+    /// Lamports paid from the owner's credit per owner-issued or purchased
+    /// grant pruned by a cranker, see `MAX_BOUNTIED_PRUNES`
+    pub prune_bounty: u64,
+    /// Protocol fee on access sales in basis points
+    pub protocol_fee_bps: u16,
+    /// Last update timestamp
+    pub updated_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ProgramConfig {
+    pub const SCHEMA_VERSION: u8 = 1;
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // schema_version
+        32 + // admin
+        9 +  // default_storage_quota (1 + 8)
+        32 + // treasury
+        8 +  // base_storage_fee
+        8 +  // fee_per_kib
+        1 + 32 + // compliance_authority
+        8 +  // prune_bounty
+        2 +  // protocol_fee_bps
+        8 +  // updated_at
+        1;   // bump
+
+    /// Apply admin-supplied parameters
+    pub fn apply(&mut self, params: ConfigParams) -> Result<()> {
+        require!(
+            params.protocol_fee_bps as u64 <= BPS_DENOMINATOR,
+            MemoryAssetError::InvalidFeeBps
+        );
+
+        self.admin = params.admin;
+        self.default_storage_quota = params.default_storage_quota;
+        self.treasury = params.treasury;
+        self.base_storage_fee = params.base_storage_fee;
+        self.fee_per_kib = params.fee_per_kib;
+        self.compliance_authority = params.compliance_authority;
+        self.prune_bounty = params.prune_bounty;
+        self.protocol_fee_bps = params.protocol_fee_bps;
+        Ok(())
+    }
+
+    /// Check if `authority` is the configured compliance authority
+    pub fn is_compliance_authority(&self, authority: &Pubkey) -> bool {
+        self.compliance_authority.as_ref() == Some(authority)
+    }
+
+    /// Protocol's cut of an access sale for `price`, in the sale's currency
+    pub fn protocol_fee(&self, price: u64) -> Result<u64> {
+        let fee = price as u128 * self.protocol_fee_bps as u128 / BPS_DENOMINATOR as u128;
+        u64::try_from(fee).map_err(|_| MemoryAssetError::ArithmeticOverflow.into())
+    }
+
+    /// Storage fee in lamports for `content_size` bytes
+    pub fn storage_fee(&self, content_size: u64) -> Result<u64> {
+        storage_fee(self.base_storage_fee, self.fee_per_kib, content_size)
+    }
+}
+
+/// Flat fee plus a fee per started KiB of `content_size`
+fn storage_fee(base_storage_fee: u64, fee_per_kib: u64, content_size: u64) -> Result<u64> {
+    let kib = content_size
+        .checked_add(BYTES_PER_KIB - 1)
+        .ok_or(MemoryAssetError::ArithmeticOverflow)?
+        / BYTES_PER_KIB;
+
+    kib.checked_mul(fee_per_kib)
+        .and_then(|fee| fee.checked_add(base_storage_fee))
+        .ok_or_else(|| MemoryAssetError::ArithmeticOverflow.into())
+}
+
+/// Admin-supplied program config parameters
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ConfigParams {
+    /// Admin allowed to update the config
+    pub admin: Pubkey,
+    /// Default per-user storage quota in bytes (None = unlimited)
+    pub default_storage_quota: Option<u64>,
+    /// Account receiving storage fees
+    pub treasury: Pubkey,
+    /// Flat fee in lamports charged per minted memory or version
+    pub base_storage_fee: u64,
+    /// Fee in lamports per started KiB of content
+    pub fee_per_kib: u64,
+    /// Authority allowed to freeze any memory for legal holds
+    pub compliance_authority: Option<Pubkey>,
+    /// Lamports paid from the owner's credit per owner-issued or purchased
+    /// grant pruned by a cranker, see `MAX_BOUNTIED_PRUNES`
+    pub prune_bounty: u64,
+    /// Protocol fee on access sales in basis points
+    pub protocol_fee_bps: u16,
+}
+
+/// Prepaid lamport credit used to pay storage fees
+#[account]
+pub struct CreditAccount {
+    /// Account layout version
+    pub schema_version: u8,
+    /// Owner of the credit
+    pub owner: Pubkey,
+    /// Spendable lamports held above rent exemption
+    pub balance: u64,
+    /// Lifetime lamports deposited
+    pub total_deposited: u64,
+    /// Lifetime lamports spent on fees
+    pub total_spent: u64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl CreditAccount {
+    pub const SCHEMA_VERSION: u8 = 1;
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // schema_version
+        32 + // owner
+        8 +  // balance
+        8 +  // total_deposited
+        8 +  // total_spent
+        1;   // bump
 }
 
 /// Memory asset metadata stored in Merkle tree leaf
@@ -32,8 +207,8 @@ impl UserAccount {
 pub struct MemoryMetadata {
     /// Owner of the memory asset
     pub owner: Pubkey,
-    /// Arweave transaction ID
-    pub arweave_id: String,
+    /// Location of the memory content
+    pub storage: StorageLocator,
     /// Version number
     pub version: u32,
     /// Optional batch ID
@@ -42,31 +217,481 @@ pub struct MemoryMetadata {
     pub created_at: i64,
     /// SHA-256 hash of the content
     pub content_hash: [u8; 32],
+    /// Size of the content in bytes
+    pub content_size: u64,
+    /// Registered agent that signed the content, if any
+    pub agent: Option<Pubkey>,
+    /// Retention deadline after which the memory may be expired
+    pub expires_at: Option<i64>,
+    /// Optional memory type tag used by scoped grants
+    pub memory_type: Option<String>,
 }
 
 /// Access policy account for managing permissions
-#[account]
+///
+/// Zero-copy header followed by `capacity` `GrantSlot`s. The first
+/// `grant_count` slots are occupied and kept sorted by grantee (then scope) so
+/// lookups are a binary search rather than a Borsh decode of every grant.
+#[account(zero_copy)]
 pub struct AccessPolicyAccount {
+    /// Account layout version
+    pub schema_version: u8,
+    /// Default policy (`PolicyType` discriminant)
+    pub default_policy: u8,
+    /// PDA bump seed
+    pub bump: u8,
+    /// 1 after a lockdown, until the owner next edits the policy
+    pub locked_down: u8,
+    /// Last grant ID handed out; IDs start at 1
+    pub last_grant_id: u32,
     /// Owner of this policy
     pub owner: Pubkey,
-    /// List of access grants
-    pub grants: Vec<AccessGrant>,
-    /// Default policy (deny or allow)
-    pub default_policy: PolicyType,
+    /// Number of occupied grant slots
+    pub grant_count: u32,
+    /// Number of allocated grant slots
+    pub capacity: u32,
     /// Last update timestamp
     pub updated_at: i64,
-    /// PDA bump seed
-    pub bump: u8,
 }
 
 impl AccessPolicyAccount {
-    pub const MAX_GRANTS: usize = 10;
-    pub const LEN: usize = 8 + // discriminator
-        32 + // owner
-        4 + (AccessGrant::LEN * Self::MAX_GRANTS) + // grants vec
-        1 +  // default_policy
-        8 +  // updated_at
-        1;   // bump
+    pub const SCHEMA_VERSION: u8 = 2;
+    pub const HEADER_LEN: usize = 8 + // discriminator
+        std::mem::size_of::<AccessPolicyAccount>();
+
+    /// Account size for `capacity` grant slots
+    pub fn space(capacity: usize) -> usize {
+        Self::HEADER_LEN + capacity * GrantSlot::LEN
+    }
+
+    /// Split account data into the header and all allocated grant slots
+    pub fn unpack(data: &[u8]) -> (&Self, &[GrantSlot]) {
+        let (header, slots) = data.split_at(Self::HEADER_LEN);
+        (bytemuck::from_bytes(&header[8..]), bytemuck::cast_slice(slots))
+    }
+
+    /// Mutable variant of `unpack`
+    pub fn unpack_mut(data: &mut [u8]) -> (&mut Self, &mut [GrantSlot]) {
+        let (header, slots) = data.split_at_mut(Self::HEADER_LEN);
+        (
+            bytemuck::from_bytes_mut(&mut header[8..]),
+            bytemuck::cast_slice_mut(slots),
+        )
+    }
+
+    /// Check the account has been migrated to the current layout. Legacy
+    /// Borsh policies have no schema byte at this offset (a V0 policy starts
+    /// with its owner key), so they are told apart by their allocated size
+    pub fn is_current(info: &AccountInfo) -> bool {
+        let len = info.data_len();
+        if len < Self::HEADER_LEN
+            || len == AccessPolicyAccountV0::LEN
+            || len == AccessPolicyAccountV1::LEN
+            || (len - Self::HEADER_LEN) % GrantSlot::LEN != 0
+        {
+            return false;
+        }
+        info.try_borrow_data()
+            .map(|data| Self::unpack(&data).0.schema_version == Self::SCHEMA_VERSION)
+            .unwrap_or(false)
+    }
+
+    /// Bump of a migrated policy, for its `seeds` constraint. Anchor checks
+    /// seeds before `has_one` and raw constraints, so the layout is checked
+    /// here to report legacy policies as `AccountNotMigrated` instead of
+    /// reading a bump out of Borsh data
+    pub fn current_bump(loader: &AccountLoader<Self>) -> Result<u8> {
+        require!(
+            Self::is_current(&loader.to_account_info()),
+            MemoryAssetError::AccountNotMigrated
+        );
+        Ok(loader.load()?.bump)
+    }
+
+    /// Check if the policy is locked down, see `lockdown`
+    pub fn is_locked_down(&self) -> bool {
+        self.locked_down != 0
+    }
+
+    /// Default policy applied when no grant matches
+    pub fn policy_type(&self) -> PolicyType {
+        if self.default_policy == PolicyType::Allow as u8 {
+            PolicyType::Allow
+        } else {
+            PolicyType::Deny
+        }
+    }
+
+    /// Occupied grant slots, sorted by grantee then scope
+    pub fn grants<'a>(&self, slots: &'a [GrantSlot]) -> &'a [GrantSlot] {
+        &slots[..self.grant_count as usize]
+    }
+
+    /// All grants held by `grantee`
+    pub fn grants_for<'a>(&self, slots: &'a [GrantSlot], grantee: &Pubkey) -> &'a [GrantSlot] {
+        let grants = self.grants(slots);
+        let start = grants.partition_point(|slot| slot.grantee < *grantee);
+        let end = grants.partition_point(|slot| slot.grantee <= *grantee);
+        &grants[start..end]
+    }
+
+    /// Check if `key` may edit this policy: the owner, or a holder of an
+    /// unexpired policy-wide `ADMIN` allow grant without a matching deny
+    pub fn is_policy_admin(&self, slots: &[GrantSlot], key: &Pubkey, current_time: i64) -> bool {
+        if *key == self.owner {
+            return true;
+        }
+
+        let admin_grants = || {
+            self.grants_for(slots, key)
+                .iter()
+                .filter(|grant| grant.permissions().contains(Permissions::ADMIN))
+        };
+        let denied = admin_grants()
+            .any(|grant| grant.is_deny() && grant.is_in_effect(current_time));
+        !denied
+            && admin_grants().any(|grant| {
+                !grant.is_deny()
+                    && grant.scope_kind == GrantSlot::SCOPE_ALL
+                    && grant.is_valid(current_time)
+            })
+    }
+
+    /// Binary search the occupied slots for the grant with the same grantee
+    /// and scope as `slot`
+    pub fn find_grant(
+        &self,
+        slots: &[GrantSlot],
+        slot: &GrantSlot,
+    ) -> std::result::Result<usize, usize> {
+        self.grants(slots)
+            .binary_search_by(|probe| probe.key().cmp(&slot.key()))
+    }
+
+    /// Hand out a new grant ID
+    pub fn next_grant_id(&mut self) -> Result<u32> {
+        self.last_grant_id = self
+            .last_grant_id
+            .checked_add(1)
+            .ok_or(MemoryAssetError::ArithmeticOverflow)?;
+        Ok(self.last_grant_id)
+    }
+
+    /// Insert or replace the grant for `slot`'s grantee and scope, keeping
+    /// slots sorted. A replaced grant keeps its ID so its delegations stay
+    /// attached.
+    pub fn upsert_grant(&mut self, slots: &mut [GrantSlot], mut slot: GrantSlot) -> Result<()> {
+        match self.find_grant(slots, &slot) {
+            Ok(index) => {
+                slot.grant_id = match slots[index].grant_id {
+                    0 => self.next_grant_id()?,
+                    grant_id => grant_id,
+                };
+                slots[index] = slot;
+            }
+            Err(index) => {
+                slot.grant_id = self.next_grant_id()?;
+                let count = self.grant_count as usize;
+                require!(
+                    count < slots.len(),
+                    MemoryAssetError::MaxAccessGrantsExceeded
+                );
+                slots.copy_within(index..count, index + 1);
+                slots[index] = slot;
+                self.grant_count += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove every grant matching `predicate`, returning how many were removed
+    pub fn remove_grants(
+        &mut self,
+        slots: &mut [GrantSlot],
+        predicate: impl Fn(&GrantSlot) -> bool,
+    ) -> usize {
+        let count = self.grant_count as usize;
+        let mut kept = 0;
+        for index in 0..count {
+            if !predicate(&slots[index]) {
+                slots[kept] = slots[index];
+                kept += 1;
+            }
+        }
+        for slot in &mut slots[kept..count] {
+            *slot = bytemuck::Zeroable::zeroed();
+        }
+        self.grant_count = kept as u32;
+        count - kept
+    }
+
+    /// Check if a deny grant in effect blocks anything `slot` would allow
+    pub fn is_denied(&self, slots: &[GrantSlot], slot: &GrantSlot, current_time: i64) -> bool {
+        self.grants_for(slots, &slot.grantee).iter().any(|grant| {
+            grant.is_deny()
+                && grant.is_in_effect(current_time)
+                && grant.scope_covers(slot)
+                && grant.permissions().intersects(slot.permissions())
+        })
+    }
+
+    /// Check if `slot` was delegated from one of `key`'s grants
+    pub fn is_delegated_by(&self, slots: &[GrantSlot], key: &Pubkey, slot: &GrantSlot) -> bool {
+        slot.parent_id != 0
+            && self
+                .grants_for(slots, key)
+                .iter()
+                .any(|parent| parent.grant_id == slot.parent_id)
+    }
+
+    /// Indexes of the grants `slot` was delegated through, nearest first
+    pub fn ancestors(&self, slots: &[GrantSlot], slot: &GrantSlot) -> Vec<usize> {
+        let grants = self.grants(slots);
+        let mut chain = Vec::new();
+        let mut parent_id = slot.parent_id;
+        while parent_id != 0 && chain.len() < MAX_DELEGATION_DEPTH as usize {
+            match grants.iter().position(|grant| grant.grant_id == parent_id) {
+                Some(index) => {
+                    chain.push(index);
+                    parent_id = grants[index].parent_id;
+                }
+                None => break,
+            }
+        }
+        chain
+    }
+
+    /// Index of a grant held by `delegator` that may issue `child`: a valid
+    /// allow grant holding `SHARE` and every permission of `child`, covering
+    /// its scope, whose expiry and remaining accesses are no tighter
+    pub fn find_delegation_parent(
+        &self,
+        slots: &[GrantSlot],
+        delegator: &Pubkey,
+        child: &GrantSlot,
+        current_time: i64,
+    ) -> Option<usize> {
+        let grants = self.grants(slots);
+        let start = grants.partition_point(|slot| slot.grantee < *delegator);
+        let required = child.permissions() | Permissions::SHARE;
+        grants[start..]
+            .iter()
+            .take_while(|parent| parent.grantee == *delegator)
+            .position(|parent| {
+                !parent.is_deny()
+                    && parent.is_valid(current_time)
+                    && parent.permissions().contains(required)
+                    && parent.scope_covers(child)
+                    && (parent.expires_at == 0
+                        || (child.expires_at != 0 && child.expires_at <= parent.expires_at))
+                    && (parent.max_access == 0
+                        || (child.max_access != 0
+                            && child.max_access <= parent.max_access - parent.current_access))
+                    && child.not_before >= parent.not_before
+                    && (parent.quota_max == 0
+                        || (child.quota_period == parent.quota_period
+                            && child.quota_max != 0
+                            && child.quota_max <= parent.quota_max))
+            })
+            .map(|offset| start + offset)
+    }
+
+    /// Remove delegated grants whose parent is gone or no longer holds
+    /// `SHARE`, transitively, returning how many were removed
+    pub fn prune_orphans(&mut self, slots: &mut [GrantSlot]) -> usize {
+        let mut total = 0;
+        loop {
+            let mut sharers: Vec<u32> = self
+                .grants(slots)
+                .iter()
+                .filter(|slot| {
+                    slot.grant_id != 0
+                        && !slot.is_deny()
+                        && slot.permissions().contains(Permissions::SHARE)
+                })
+                .map(|slot| slot.grant_id)
+                .collect();
+            sharers.sort_unstable();
+
+            let removed = self.remove_grants(slots, |slot| {
+                slot.parent_id != 0 && sharers.binary_search(&slot.parent_id).is_err()
+            });
+            if removed == 0 {
+                return total;
+            }
+            total += removed;
+        }
+    }
+}
+
+/// Fixed-size grant stored in an access policy's slot array
+#[zero_copy]
+pub struct GrantSlot {
+    /// Grantee's public key
+    pub grantee: Pubkey,
+    /// Expiration timestamp, 0 if the grant never expires
+    pub expires_at: i64,
+    /// Maximum access count, 0 if unlimited
+    pub max_access: u32,
+    /// Current access count
+    pub current_access: u32,
+    /// Permissions bitmap, see `Permissions`
+    pub permissions: u8,
+    /// Scope kind, one of the `SCOPE_*` constants
+    pub scope_kind: u8,
+    /// Grant effect (`GrantEffect` discriminant)
+    pub effect: u8,
+    pub _padding: [u8; 5],
+    /// Asset ID, or hash of the batch ID or memory type, depending on the kind
+    pub scope_target: [u8; 32],
+    /// Grant ID, unique within the policy
+    pub grant_id: u32,
+    /// ID of the grant this one was delegated from, 0 if issued by the owner
+    pub parent_id: u32,
+    /// Delegation depth, 0 if issued by the owner
+    pub depth: u8,
+    /// How the grant was issued, one of the `ORIGIN_*` constants
+    pub origin: u8,
+    pub _padding2: [u8; 6],
+    /// Time the grant starts, 0 if immediately
+    pub not_before: i64,
+    /// Start of the quota period `period_used` counts against
+    pub period_start: i64,
+    /// Quota period length in seconds, 0 if the grant has no quota
+    pub quota_period: u32,
+    /// Accesses allowed per quota period
+    pub quota_max: u32,
+    /// Accesses used in the period starting at `period_start`
+    pub period_used: u32,
+    pub _padding3: [u8; 4],
+    /// Reserved for future grant fields
+    pub _reserved: [u8; 16],
+}
+
+impl GrantSlot {
+    pub const LEN: usize = std::mem::size_of::<GrantSlot>();
+
+    pub const SCOPE_ALL: u8 = 0;
+    pub const SCOPE_ASSET: u8 = 1;
+    pub const SCOPE_BATCH: u8 = 2;
+    pub const SCOPE_MEMORY_TYPE: u8 = 3;
+
+    pub const ORIGIN_OWNER: u8 = 0;
+    pub const ORIGIN_PURCHASE: u8 = 1;
+    /// Written by a template binding, which alone rewrites or removes it
+    pub const ORIGIN_TEMPLATE: u8 = 2;
+
+    /// Sort key: grantee, then scope
+    pub fn key(&self) -> (&Pubkey, u8, &[u8; 32]) {
+        (&self.grantee, self.scope_kind, &self.scope_target)
+    }
+
+    /// Check if the grant's scope includes everything `other`'s scope does
+    pub fn scope_covers(&self, other: &GrantSlot) -> bool {
+        self.scope_kind == Self::SCOPE_ALL
+            || (self.scope_kind == other.scope_kind && self.scope_target == other.scope_target)
+    }
+
+    /// Check if the grant covers `memory`
+    pub fn matches(&self, memory: &MemoryAccount) -> bool {
+        match self.scope_kind {
+            Self::SCOPE_ALL => true,
+            Self::SCOPE_ASSET => self.scope_target == memory.asset_id.to_bytes(),
+            Self::SCOPE_BATCH => memory
+                .batch_id
+                .as_deref()
+                .map_or(false, |batch_id| self.scope_target == scope_hash(batch_id)),
+            Self::SCOPE_MEMORY_TYPE => memory
+                .memory_type
+                .as_deref()
+                .map_or(false, |memory_type| self.scope_target == scope_hash(memory_type)),
+            _ => false,
+        }
+    }
+
+    /// Typed permissions of the grant
+    pub fn permissions(&self) -> Permissions {
+        Permissions::from_bits_truncate(self.permissions)
+    }
+
+    /// Check if the grant takes permissions away rather than adding them
+    pub fn is_deny(&self) -> bool {
+        self.effect == GrantEffect::Deny as u8
+    }
+
+    /// Check if grant is expired
+    pub fn is_expired(&self, current_time: i64) -> bool {
+        self.expires_at != 0 && current_time > self.expires_at
+    }
+
+    /// Check if grant has not started yet
+    pub fn is_pending(&self, current_time: i64) -> bool {
+        current_time < self.not_before
+    }
+
+    /// Check if the grant has started and not expired
+    pub fn is_in_effect(&self, current_time: i64) -> bool {
+        !self.is_pending(current_time) && !self.is_expired(current_time)
+    }
+
+    /// Check if grant has reached max access
+    pub fn is_access_exhausted(&self) -> bool {
+        self.max_access != 0 && self.current_access >= self.max_access
+    }
+
+    /// Check if the quota for the current period is used up; the quota
+    /// refills once the period starting at `period_start` has passed
+    pub fn is_quota_exhausted(&self, current_time: i64) -> bool {
+        self.quota_max != 0
+            && self.period_used >= self.quota_max
+            && current_time < self.period_start.saturating_add(self.quota_period as i64)
+    }
+
+    /// Count one use against the access limit and the period quota,
+    /// starting a new period once the current one has passed
+    pub fn record_access(&mut self, current_time: i64) {
+        if self.quota_period != 0
+            && current_time >= self.period_start.saturating_add(self.quota_period as i64)
+        {
+            self.period_start = current_time;
+            self.period_used = 0;
+        }
+        self.period_used = self.period_used.saturating_add(1);
+        self.current_access = self.current_access.saturating_add(1);
+    }
+
+    /// Check if grant is valid
+    pub fn is_valid(&self, current_time: i64) -> bool {
+        self.is_in_effect(current_time)
+            && !self.is_access_exhausted()
+            && !self.is_quota_exhausted(current_time)
+    }
+
+    /// Check if the grant can never be used again and may be pruned. Grants
+    /// that have not started or are waiting for their quota to refill stay.
+    pub fn is_prunable(&self, current_time: i64) -> bool {
+        self.is_expired(current_time) || (!self.is_deny() && self.is_access_exhausted())
+    }
+
+    /// Reason the grant cannot be used at `current_time`, if any
+    pub fn invalid_reason(&self, current_time: i64) -> Option<MemoryAssetError> {
+        if self.is_expired(current_time) {
+            Some(MemoryAssetError::AccessGrantExpired)
+        } else if self.is_pending(current_time) {
+            Some(MemoryAssetError::AccessGrantNotYetValid)
+        } else if self.is_access_exhausted() {
+            Some(MemoryAssetError::AccessGrantExhausted)
+        } else if self.is_quota_exhausted(current_time) {
+            Some(MemoryAssetError::AccessGrantQuotaExceeded)
+        } else {
+            None
+        }
+    }
+}
+
+/// Hash a batch ID or memory type into a grant slot's scope target
+pub fn scope_hash(value: &str) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hash(value.as_bytes()).to_bytes()
 }
 
 /// Access grant for a specific grantee
@@ -74,7 +699,7 @@ impl AccessPolicyAccount {
 pub struct AccessGrant {
     /// Grantee's public key
     pub grantee: Pubkey,
-    /// Permissions bitmap (read=1, write=2, transfer=4)
+    /// Permissions bitmap, see `Permissions`
     pub permissions: u8,
     /// Optional expiration timestamp
     pub expires_at: Option<i64>,
@@ -82,28 +707,59 @@ pub struct AccessGrant {
     pub max_access: Option<u32>,
     /// Current access count
     pub current_access: u32,
+    /// Memories the grant applies to
+    pub scope: GrantScope,
+    /// Whether the grant adds or takes away `permissions`
+    pub effect: GrantEffect,
+    /// Optional start timestamp
+    pub not_before: Option<i64>,
+    /// Optional recurring access limit
+    pub quota: Option<AccessQuota>,
 }
 
 impl AccessGrant {
-    pub const LEN: usize = 32 + // grantee
-        1 +  // permissions
-        9 +  // expires_at (1 + 8)
-        5 +  // max_access (1 + 4)
-        4;   // current_access
-
-    /// Check if grant has read permission
-    pub fn can_read(&self) -> bool {
-        self.permissions & 0b001 != 0
+    /// Typed permissions of the grant, rejecting unknown bits
+    pub fn permissions(&self) -> Result<Permissions> {
+        Permissions::validate(self.permissions)
     }
 
-    /// Check if grant has write permission
-    pub fn can_write(&self) -> bool {
-        self.permissions & 0b010 != 0
-    }
+    /// Validate a grant before it is written to `owner`'s policy
+    pub fn validate(&self, owner: &Pubkey, current_time: i64) -> Result<Permissions> {
+        let permissions = self.permissions()?;
+
+        // Validate expiration timestamp if present
+        if let Some(expires_at) = self.expires_at {
+            require!(expires_at > current_time, MemoryAssetError::InvalidTimestamp);
+        }
+
+        // A zero limit is indistinguishable from unlimited in a grant slot
+        require!(
+            self.max_access != Some(0),
+            MemoryAssetError::InvalidMaxAccess
+        );
+
+        // The grant must start before it expires
+        if let (Some(not_before), Some(expires_at)) = (self.not_before, self.expires_at) {
+            require!(not_before < expires_at, MemoryAssetError::InvalidTimestamp);
+        }
 
-    /// Check if grant has transfer permission
-    pub fn can_transfer(&self) -> bool {
-        self.permissions & 0b100 != 0
+        if let Some(quota) = &self.quota {
+            quota.validate()?;
+        }
+
+        // Deny grants must name what they deny and are never used up
+        if self.effect == GrantEffect::Deny {
+            require!(!permissions.is_empty(), MemoryAssetError::InvalidPermissions);
+            require!(self.max_access.is_none(), MemoryAssetError::InvalidMaxAccess);
+            require!(self.quota.is_none(), MemoryAssetError::InvalidAccessQuota);
+        }
+
+        // Validate that grantee is not the owner
+        require!(self.grantee != *owner, MemoryAssetError::InvalidOwner);
+
+        self.scope.validate()?;
+
+        Ok(permissions)
     }
 
     /// Check if grant is expired
@@ -124,28 +780,849 @@ impl AccessGrant {
         }
     }
 
+    /// Check if grant has not started yet
+    pub fn is_pending(&self, current_time: i64) -> bool {
+        self.not_before.map_or(false, |not_before| current_time < not_before)
+    }
+
     /// Check if grant is valid
     pub fn is_valid(&self, current_time: i64) -> bool {
-        !self.is_expired(current_time) && !self.is_access_exhausted()
+        !self.is_expired(current_time)
+            && !self.is_pending(current_time)
+            && !self.is_access_exhausted()
+    }
+
+    /// Encode the grant as slots, one per scope target
+    pub fn to_slots(&self) -> Vec<GrantSlot> {
+        self.scope
+            .targets()
+            .into_iter()
+            .map(|(scope_kind, scope_target)| GrantSlot {
+                grantee: self.grantee,
+                expires_at: self.expires_at.unwrap_or(0),
+                max_access: self.max_access.unwrap_or(0),
+                current_access: self.current_access,
+                permissions: self.permissions,
+                scope_kind,
+                effect: self.effect as u8,
+                _padding: [0; 5],
+                scope_target,
+                grant_id: 0,
+                parent_id: 0,
+                depth: 0,
+                origin: GrantSlot::ORIGIN_OWNER,
+                _padding2: [0; 6],
+                not_before: self.not_before.unwrap_or(0),
+                period_start: 0,
+                quota_period: self.quota.as_ref().map_or(0, |quota| quota.period),
+                quota_max: self.quota.as_ref().map_or(0, |quota| quota.max_access),
+                period_used: 0,
+                _padding3: [0; 4],
+                _reserved: [0; 16],
+            })
+            .collect()
     }
 }
 
-/// Policy type enum
+/// Recurring access limit, e.g. 100 accesses per day
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct AccessQuota {
+    /// Accesses allowed per period
+    pub max_access: u32,
+    /// Period length in seconds
+    pub period: u32,
+}
+
+impl AccessQuota {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.max_access > 0 && self.period > 0,
+            MemoryAssetError::InvalidAccessQuota
+        );
+        Ok(())
+    }
+}
+
+/// Effect of an access grant
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+pub enum GrantEffect {
+    /// Grants `permissions`
+    Allow,
+    /// Denies `permissions`, overriding allow grants and the default policy
+    Deny,
+}
+
+/// Memories an access grant applies to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum GrantScope {
+    /// Every memory under the policy
+    All,
+    /// Specific assets
+    Assets(Vec<Pubkey>),
+    /// Memories minted in a batch, matching `MemoryMetadata.batch_id`
+    Batch(String),
+    /// Memories of a type, matching `MemoryMetadata.memory_type`
+    MemoryType(String),
+}
+
+impl GrantScope {
+    /// Largest Borsh-encoded scope: the enum tag and a full asset list
+    pub const MAX_LEN: usize = 1 + 4 + 32 * MAX_GRANT_SCOPE_ASSETS;
+
+    /// Validate the scope's targets
+    pub fn validate(&self) -> Result<()> {
+        let valid = match self {
+            GrantScope::All => true,
+            GrantScope::Assets(asset_ids) => {
+                !asset_ids.is_empty() && asset_ids.len() <= MAX_GRANT_SCOPE_ASSETS
+            }
+            GrantScope::Batch(batch_id) => {
+                !batch_id.is_empty() && batch_id.len() <= MAX_BATCH_ID_LEN
+            }
+            GrantScope::MemoryType(memory_type) => {
+                !memory_type.is_empty() && memory_type.len() <= MAX_MEMORY_TYPE_LEN
+            }
+        };
+        require!(valid, MemoryAssetError::InvalidGrantScope);
+        Ok(())
+    }
+
+    /// Check if the scope includes `memory`
+    pub fn covers(&self, memory: &MemoryAccount) -> bool {
+        match self {
+            GrantScope::All => true,
+            GrantScope::Assets(asset_ids) => asset_ids.contains(&memory.asset_id),
+            GrantScope::Batch(batch_id) => memory.batch_id.as_deref() == Some(batch_id.as_str()),
+            GrantScope::MemoryType(memory_type) => {
+                memory.memory_type.as_deref() == Some(memory_type.as_str())
+            }
+        }
+    }
+
+    /// Slot scope kind and target for each memory set the scope covers
+    pub fn targets(&self) -> Vec<(u8, [u8; 32])> {
+        match self {
+            GrantScope::All => vec![(GrantSlot::SCOPE_ALL, [0; 32])],
+            GrantScope::Assets(asset_ids) => asset_ids
+                .iter()
+                .map(|asset_id| (GrantSlot::SCOPE_ASSET, asset_id.to_bytes()))
+                .collect(),
+            GrantScope::Batch(batch_id) => {
+                vec![(GrantSlot::SCOPE_BATCH, scope_hash(batch_id))]
+            }
+            GrantScope::MemoryType(memory_type) => {
+                vec![(GrantSlot::SCOPE_MEMORY_TYPE, scope_hash(memory_type))]
+            }
+        }
+    }
+}
+
+/// Grants to remove from a policy
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct GrantRevocation {
+    /// Grantee whose grants are removed
+    pub grantee: Pubkey,
+    /// Scope to remove, or every grant held by the grantee if `None`
+    pub scope: Option<GrantScope>,
+}
+
+impl GrantRevocation {
+    /// Check if the revocation covers `slot`
+    pub fn covers(&self, slot: &GrantSlot) -> bool {
+        slot.grantee == self.grantee
+            && self.scope.as_ref().map_or(true, |scope| {
+                scope
+                    .targets()
+                    .iter()
+                    .any(|(kind, target)| slot.scope_kind == *kind && slot.scope_target == *target)
+            })
+    }
+}
+
+/// Policy type enum
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
 pub enum PolicyType {
     Deny,
     Allow,
 }
 
+/// On-chain record of a minted memory asset
+#[account]
+pub struct MemoryAccount {
+    /// Account layout version
+    pub schema_version: u8,
+    /// Bubblegum asset ID of the memory
+    pub asset_id: Pubkey,
+    /// Current owner of the memory asset
+    pub owner: Pubkey,
+    /// Original minter; sets the royalty shares until the first sale
+    pub creator: Pubkey,
+    /// Latest version number
+    pub current_version: u32,
+    /// Optional batch ID
+    pub batch_id: Option<String>,
+    /// Optional memory type tag
+    pub memory_type: Option<String>,
+    /// Lifecycle status
+    pub status: MemoryStatus,
+    /// Authority that froze the memory, if frozen
+    pub frozen_by: Option<Pubkey>,
+    /// Retention deadline after which anyone may expire the memory
+    pub expires_at: Option<i64>,
+    /// Creator royalties paid on sales
+    pub royalties: Vec<RoyaltyShare>,
+    /// Set by the first sale; the royalty shares can no longer change
+    pub royalties_locked: bool,
+    /// Listing PDA while listed for sale
+    pub delegate: Option<Pubkey>,
+    /// Creation timestamp
+    pub created_at: i64,
+    /// Last update timestamp
+    pub updated_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl MemoryAccount {
+    pub const SCHEMA_VERSION: u8 = 1;
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // schema_version
+        32 + // asset_id
+        32 + // owner
+        32 + // creator
+        4 +  // current_version
+        1 + 4 + MAX_BATCH_ID_LEN + // batch_id
+        1 + 4 + MAX_MEMORY_TYPE_LEN + // memory_type
+        1 +  // status
+        1 + 32 + // frozen_by
+        1 + 8 +  // expires_at
+        4 + RoyaltyShare::LEN * MAX_ROYALTY_RECIPIENTS + // royalties
+        1 +  // royalties_locked
+        1 + 32 + // delegate
+        8 +  // created_at
+        8 +  // updated_at
+        1;   // bump
+
+    /// Check if the memory is under a freeze
+    pub fn is_frozen(&self) -> bool {
+        self.frozen_by.is_some()
+    }
+
+    /// Check if the retention deadline has passed
+    pub fn is_expired(&self, current_time: i64) -> bool {
+        if let Some(expires_at) = self.expires_at {
+            current_time >= expires_at
+        } else {
+            false
+        }
+    }
+
+    /// Check if the memory is listed for sale
+    pub fn is_listed(&self) -> bool {
+        self.delegate.is_some()
+    }
+
+    /// Ensure the memory is active and not under a freeze
+    pub fn require_active(&self) -> Result<()> {
+        require!(
+            self.status == MemoryStatus::Active,
+            MemoryAssetError::MemoryNotActive
+        );
+        require!(!self.is_frozen(), MemoryAssetError::MemoryFrozen);
+        Ok(())
+    }
+
+    /// Ensure the memory may be transferred, burned or re-versioned; a
+    /// listed memory stays as listed until it is sold or delisted
+    pub fn require_mutable(&self) -> Result<()> {
+        self.require_active()?;
+        require!(!self.is_listed(), MemoryAssetError::MemoryListed);
+        Ok(())
+    }
+
+    /// Royalty owed to each recipient on a sale of `amount`
+    pub fn royalty_payouts(&self, amount: u64) -> Result<Vec<(Pubkey, u64)>> {
+        self.royalties
+            .iter()
+            .map(|share| Ok((share.recipient, share.of(amount)?)))
+            .collect()
+    }
+}
+
+/// Share of sale proceeds paid to a royalty recipient
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoyaltyShare {
+    /// Account receiving the royalty
+    pub recipient: Pubkey,
+    /// Basis points of the sale
+    pub bps: u16,
+}
+
+impl RoyaltyShare {
+    pub const LEN: usize = 32 + 2;
+
+    /// Royalty on a sale of `amount`, rounded down
+    pub fn of(&self, amount: u64) -> Result<u64> {
+        let royalty = amount as u128 * self.bps as u128 / BPS_DENOMINATOR as u128;
+        u64::try_from(royalty).map_err(|_| MemoryAssetError::ArithmeticOverflow.into())
+    }
+
+    /// Validate a memory's royalty shares: bounded recipients, no
+    /// duplicates and a combined rate within `MAX_ROYALTY_BPS`
+    pub fn validate_all(royalties: &[RoyaltyShare]) -> Result<()> {
+        require!(
+            royalties.len() <= MAX_ROYALTY_RECIPIENTS,
+            MemoryAssetError::InvalidRoyalties
+        );
+
+        let mut total_bps: u16 = 0;
+        for (i, share) in royalties.iter().enumerate() {
+            require!(
+                share.bps > 0
+                    && share.recipient != Pubkey::default()
+                    && royalties[..i].iter().all(|other| other.recipient != share.recipient),
+                MemoryAssetError::InvalidRoyalties
+            );
+            total_bps = total_bps
+                .checked_add(share.bps)
+                .ok_or(MemoryAssetError::InvalidRoyalties)?;
+        }
+        require!(total_bps <= MAX_ROYALTY_BPS, MemoryAssetError::InvalidRoyalties);
+
+        Ok(())
+    }
+}
+
+/// Memory lifecycle status
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MemoryStatus {
+    Active,
+    Burned,
+    /// Tombstoned after its retention deadline
+    Expired,
+    /// Erased on request; content is no longer served
+    Redacted,
+}
+
 /// Memory version record
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct MemoryVersion {
+#[account]
+pub struct MemoryVersionAccount {
+    /// Account layout version
+    pub schema_version: u8,
+    /// Asset ID of the memory this version belongs to
+    pub asset_id: Pubkey,
     /// Version number
     pub version: u32,
-    /// Arweave transaction ID
-    pub arweave_id: String,
-    /// Content hash
+    /// Content hash shared by every locator
     pub content_hash: [u8; 32],
+    /// Size of the content in bytes
+    pub content_size: u64,
+    /// Storage locators, primary first followed by mirrors
+    pub locators: Vec<StorageLocator>,
+    /// When the content was redacted; locators are cleared at that point
+    pub redacted_at: Option<i64>,
     /// Creation timestamp
     pub created_at: i64,
+    /// Last update timestamp
+    pub updated_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl MemoryVersionAccount {
+    pub const SCHEMA_VERSION: u8 = 1;
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // schema_version
+        32 + // asset_id
+        4 +  // version
+        32 + // content_hash
+        8 +  // content_size
+        4 + (StorageLocator::MAX_LEN * MAX_STORAGE_LOCATORS) + // locators vec
+        1 + 8 +  // redacted_at
+        8 +  // created_at
+        8 +  // updated_at
+        1;   // bump
+
+    /// Primary storage locator
+    pub fn primary(&self) -> Option<&StorageLocator> {
+        self.locators.first()
+    }
+
+    /// Check if the content has been redacted
+    pub fn is_redacted(&self) -> bool {
+        self.redacted_at.is_some()
+    }
+}
+
+/// Location of memory content on a storage backend
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum StorageLocator {
+    /// Arweave transaction ID
+    Arweave { tx_id: String },
+    /// IPFS content identifier (CIDv0 or base32 CIDv1)
+    Ipfs { cid: String },
+    /// Generic URI, e.g. a Shadow Drive file URL
+    Uri { uri: String },
+}
+
+impl StorageLocator {
+    pub const MAX_LEN: usize = 1 + // variant
+        4 + MAX_STORAGE_URI_LEN; // longest string payload
+
+    /// Validate the locator according to its backend's format
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            StorageLocator::Arweave { tx_id } => {
+                require!(
+                    tx_id.len() == ARWEAVE_ID_LEN,
+                    MemoryAssetError::InvalidArweaveIdLength
+                );
+                require!(
+                    tx_id
+                        .bytes()
+                        .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'),
+                    MemoryAssetError::InvalidArweaveId
+                );
+            }
+            StorageLocator::Ipfs { cid } => {
+                let valid = if cid.starts_with("Qm") {
+                    // CIDv0: base58btc-encoded SHA-256 multihash
+                    cid.len() == 46
+                        && cid.bytes().all(|b| {
+                            b.is_ascii_alphanumeric() && !matches!(b, b'0' | b'O' | b'I' | b'l')
+                        })
+                } else {
+                    // CIDv1: multibase prefix 'b' followed by lowercase base32
+                    cid.len() <= MAX_IPFS_CID_LEN
+                        && cid.len() > 1
+                        && cid.starts_with('b')
+                        && cid
+                            .bytes()
+                            .all(|b| b.is_ascii_lowercase() || (b'2'..=b'7').contains(&b))
+                };
+                require!(valid, MemoryAssetError::InvalidIpfsCid);
+            }
+            StorageLocator::Uri { uri } => {
+                require!(
+                    uri.len() <= MAX_STORAGE_URI_LEN,
+                    MemoryAssetError::InvalidStorageUri
+                );
+                let scheme_valid = match uri.split_once("://") {
+                    Some((scheme, rest)) => !scheme.is_empty() && !rest.is_empty(),
+                    None => false,
+                };
+                require!(scheme_valid, MemoryAssetError::InvalidStorageUri);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Third-party attestation on a memory asset
+#[account]
+pub struct Attestation {
+    /// Account layout version
+    pub schema_version: u8,
+    /// Asset ID of the attested memory
+    pub asset_id: Pubkey,
+    /// Verifier issuing the attestation
+    pub attester: Pubkey,
+    /// Kind of claim being made
+    pub claim_type: ClaimType,
+    /// Score between 0 and MAX_ATTESTATION_SCORE
+    pub score: u8,
+    /// Optional URI pointing to supporting evidence
+    pub evidence_uri: Option<String>,
+    /// Attestation timestamp
+    pub created_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl Attestation {
+    pub const SCHEMA_VERSION: u8 = 1;
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // schema_version
+        32 + // asset_id
+        32 + // attester
+        1 +  // claim_type
+        1 +  // score
+        1 + 4 + MAX_EVIDENCE_URI_LEN + // evidence_uri
+        8 +  // created_at
+        1;   // bump
+}
+
+/// Claim type enum for attestations
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ClaimType {
+    /// Content was fact-checked for accuracy
+    Accuracy,
+    /// Content origin was verified
+    Provenance,
+    /// Content was reviewed for harmful material
+    Safety,
+    /// Content was reviewed by a human
+    HumanReview,
+}
+
+/// Agent key registered by an owner to sign memory content
+#[account]
+pub struct AgentAccount {
+    /// Account layout version
+    pub schema_version: u8,
+    /// Owner the agent acts for
+    pub owner: Pubkey,
+    /// Agent's Ed25519 signing key
+    pub agent: Pubkey,
+    /// Registration timestamp
+    pub registered_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl AgentAccount {
+    pub const SCHEMA_VERSION: u8 = 1;
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // schema_version
+        32 + // owner
+        32 + // agent
+        8 +  // registered_at
+        1;   // bump
+}
+
+/// Named set of grants and a default policy that owners can apply to their
+/// policy. The authority may be a user or an organization's multisig.
+#[account]
+pub struct PolicyTemplate {
+    /// Account layout version
+    pub schema_version: u8,
+    /// Key allowed to edit the template
+    pub authority: Pubkey,
+    /// Template name, unique per authority
+    pub name: String,
+    /// Grants written to policies the template is applied to
+    pub grants: Vec<AccessGrant>,
+    /// Default policy set on owner-wide applications
+    pub default_policy: PolicyType,
+    /// Incremented on every update
+    pub version: u32,
+    /// Creation timestamp
+    pub created_at: i64,
+    /// Last update timestamp
+    pub updated_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl PolicyTemplate {
+    pub const SCHEMA_VERSION: u8 = 1;
+
+    /// Account size for a template named `name` holding `grants`
+    pub fn space(name: &str, grants: &[AccessGrant]) -> usize {
+        let grants_len: usize = grants
+            .iter()
+            .map(|grant| grant.try_to_vec().map_or(0, |bytes| bytes.len()))
+            .sum();
+        8 + // discriminator
+        1 +  // schema_version
+        32 + // authority
+        4 + name.len() + // name
+        4 + grants_len + // grants
+        1 +  // default_policy
+        4 +  // version
+        8 +  // created_at
+        8 +  // updated_at
+        1    // bump
+    }
+
+    /// Validate template grants; templates never hand out administration
+    pub fn validate_grants(grants: &[AccessGrant], current_time: i64) -> Result<()> {
+        require!(
+            grants.len() <= MAX_TEMPLATE_GRANTS,
+            MemoryAssetError::MaxTemplateGrantsExceeded
+        );
+
+        let mut slots = 0;
+        for grant in grants {
+            // The grantee-is-owner check happens when the template is applied
+            let permissions = grant.validate(&Pubkey::default(), current_time)?;
+            require!(
+                !permissions.contains(Permissions::ADMIN),
+                MemoryAssetError::InvalidPermissions
+            );
+            slots += grant.scope.targets().len();
+        }
+        require!(
+            slots <= MAX_TEMPLATE_SLOTS,
+            MemoryAssetError::MaxTemplateGrantsExceeded
+        );
+
+        Ok(())
+    }
+}
+
+/// Record of a template applied to an owner's policy, or to one asset in it
+#[account]
+pub struct TemplateBinding {
+    /// Account layout version
+    pub schema_version: u8,
+    /// Owner whose policy the template was applied to
+    pub owner: Pubkey,
+    /// Applied template
+    pub template: Pubkey,
+    /// Asset the grants were narrowed to, None for the whole policy
+    pub asset_id: Option<Pubkey>,
+    /// Whether anyone may re-apply the template after it is updated
+    pub propagate: bool,
+    /// Whether re-applying also sets the template's default policy
+    pub sync_default_policy: bool,
+    /// Template version last applied
+    pub applied_version: u32,
+    /// Slots written by the last application, replaced on the next one as
+    /// long as they still hold what the template wrote
+    pub applied_keys: Vec<GrantKey>,
+    /// Last application timestamp
+    pub applied_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl TemplateBinding {
+    pub const SCHEMA_VERSION: u8 = 1;
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // schema_version
+        32 + // owner
+        32 + // template
+        1 + 32 + // asset_id
+        1 +  // propagate
+        1 +  // sync_default_policy
+        4 +  // applied_version
+        4 + GrantKey::LEN * MAX_TEMPLATE_SLOTS + // applied_keys
+        8 +  // applied_at
+        1;   // bump
+}
+
+/// Grantee and scope identifying a grant slot
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct GrantKey {
+    pub grantee: Pubkey,
+    pub scope_kind: u8,
+    pub scope_target: [u8; 32],
+}
+
+impl GrantKey {
+    pub const LEN: usize = 32 + 1 + 32;
+}
+
+impl From<&GrantSlot> for GrantKey {
+    fn from(slot: &GrantSlot) -> Self {
+        Self {
+            grantee: slot.grantee,
+            scope_kind: slot.scope_kind,
+            scope_target: slot.scope_target,
+        }
+    }
+}
+
+/// Pending request from a would-be grantee for access to an owner's memories
+#[account]
+pub struct AccessRequest {
+    /// Account layout version
+    pub schema_version: u8,
+    /// Owner whose memories are requested
+    pub owner: Pubkey,
+    /// Key asking for access; receives the rent back when resolved
+    pub requester: Pubkey,
+    /// Memories access is requested for
+    pub scope: GrantScope,
+    /// Requested permissions bitmap, see `Permissions`
+    pub permissions: u8,
+    /// Hash of the off-chain message accompanying the request
+    pub message_hash: [u8; 32],
+    /// Request timestamp
+    pub requested_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl AccessRequest {
+    pub const SCHEMA_VERSION: u8 = 1;
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // schema_version
+        32 + // owner
+        32 + // requester
+        GrantScope::MAX_LEN + // scope
+        1 +  // permissions
+        32 + // message_hash
+        8 +  // requested_at
+        1;   // bump
+}
+
+/// Standing offer to sell time-limited access to an owner's memories. The
+/// owner can still revoke, replace or lock down a sold grant before it
+/// expires, and no refund is paid when they do
+#[account]
+pub struct AccessOffer {
+    /// Account layout version
+    pub schema_version: u8,
+    /// Owner selling access; receives the price minus the protocol fee and royalties
+    pub owner: Pubkey,
+    /// Owner-chosen offer ID
+    pub offer_id: u64,
+    /// Memories the purchased grant covers
+    pub scope: GrantScope,
+    /// Permissions bitmap of the purchased grant, see `Permissions`
+    pub permissions: u8,
+    /// Seconds of access per purchase
+    pub duration: i64,
+    /// Price in lamports, or in base units of `payment_mint`
+    pub price: u64,
+    /// SPL token the offer is priced in, None for lamports
+    pub payment_mint: Option<Pubkey>,
+    /// Optional access limit of the purchased grant
+    pub max_access: Option<u32>,
+    /// Creator royalties on the proceeds, combined across the memories the
+    /// offer covered when it was created
+    pub royalties: Vec<RoyaltyShare>,
+    /// Number of purchases
+    pub sales: u64,
+    /// Creation timestamp
+    pub created_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl AccessOffer {
+    pub const SCHEMA_VERSION: u8 = 1;
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // schema_version
+        32 + // owner
+        8 +  // offer_id
+        GrantScope::MAX_LEN + // scope
+        1 +  // permissions
+        8 +  // duration
+        8 +  // price
+        1 + 32 + // payment_mint
+        5 +  // max_access (1 + 4)
+        4 + RoyaltyShare::LEN * MAX_OFFER_ROYALTY_RECIPIENTS + // royalties
+        8 +  // sales
+        8 +  // created_at
+        1;   // bump
+
+    /// Royalty owed to each recipient on a sale with `amount` proceeds
+    pub fn royalty_payouts(&self, amount: u64) -> Result<Vec<(Pubkey, u64)>> {
+        self.royalties
+            .iter()
+            .map(|share| Ok((share.recipient, share.of(amount)?)))
+            .collect()
+    }
+}
+
+/// SPL token accepted for access sales and storage fees
+#[account]
+pub struct AcceptedMint {
+    /// Account layout version
+    pub schema_version: u8,
+    /// Token mint
+    pub mint: Pubkey,
+    /// Treasury's token account for this mint, receiving fees
+    pub treasury_token_account: Pubkey,
+    /// Flat storage fee in base units per minted memory or version
+    pub base_storage_fee: u64,
+    /// Storage fee in base units per started KiB of content
+    pub fee_per_kib: u64,
+    /// Creation timestamp
+    pub created_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl AcceptedMint {
+    pub const SCHEMA_VERSION: u8 = 1;
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // schema_version
+        32 + // mint
+        32 + // treasury_token_account
+        8 +  // base_storage_fee
+        8 +  // fee_per_kib
+        8 +  // created_at
+        1;   // bump
+
+    /// Storage fee in base units for `content_size` bytes
+    pub fn storage_fee(&self, content_size: u64) -> Result<u64> {
+        storage_fee(self.base_storage_fee, self.fee_per_kib, content_size)
+    }
+}
+
+/// Fixed-price sale of a memory; the listing PDA is the compressed NFT's leaf
+/// delegate, and is recorded as the memory's delegate, while it exists
+#[account]
+pub struct Listing {
+    /// Account layout version
+    pub schema_version: u8,
+    /// Bubblegum asset ID of the memory for sale
+    pub asset_id: Pubkey,
+    /// Owner selling the memory; receives the price minus the protocol fee and royalties
+    pub seller: Pubkey,
+    /// Price in lamports
+    pub price: u64,
+    /// Only buyer allowed to fill the listing, None for anyone
+    pub allowed_buyer: Option<Pubkey>,
+    /// Time after which the listing can no longer be filled
+    pub expires_at: i64,
+    /// Creation timestamp
+    pub created_at: i64,
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl Listing {
+    pub const SCHEMA_VERSION: u8 = 1;
+    pub const LEN: usize = 8 + // discriminator
+        1 +  // schema_version
+        32 + // asset_id
+        32 + // seller
+        8 +  // price
+        1 + 32 + // allowed_buyer
+        8 +  // expires_at
+        8 +  // created_at
+        1;   // bump
+
+    /// Check if `buyer` may fill the listing
+    pub fn allows(&self, buyer: &Pubkey) -> bool {
+        match self.allowed_buyer {
+            Some(allowed_buyer) => allowed_buyer == *buyer,
+            None => true,
+        }
+    }
+}
+
+/// Current state of a memory's compressed NFT leaf, as served by an indexer,
+/// for the Bubblegum CPIs; the proof path goes in `remaining_accounts`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct CompressedLeaf {
+    /// Current root of the Merkle tree
+    pub root: [u8; 32],
+    /// Hash of the leaf's metadata
+    pub data_hash: [u8; 32],
+    /// Hash of the leaf's creators
+    pub creator_hash: [u8; 32],
+    /// Leaf nonce the asset ID is derived from
+    pub nonce: u64,
+    /// Leaf index in the tree
+    pub index: u32,
+}
+
+impl CompressedLeaf {
+    /// Ensure the leaf in `merkle_tree` is the memory's asset
+    pub fn require_asset(&self, merkle_tree: &Pubkey, asset_id: &Pubkey) -> Result<()> {
+        require!(
+            get_asset_id(merkle_tree, self.nonce) == *asset_id,
+            MemoryAssetError::InvalidLeaf
+        );
+        Ok(())
+    }
 }
@@ -2,6 +2,22 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { MemoryAsset } from "../target/types/memory_asset";
 import { expect } from "chai";
+import { createMint, createAccount, mintTo, getAccount } from "@solana/spl-token";
+import {
+  computeCreatorHash,
+  computeDataHash,
+  createCreateTreeInstruction,
+  createMintV1Instruction,
+  getLeafAssetId,
+  MetadataArgs,
+  TokenProgramVersion,
+  TokenStandard,
+} from "@metaplex-foundation/mpl-bubblegum";
+import {
+  ConcurrentMerkleTreeAccount,
+  emptyNode,
+  getConcurrentMerkleTreeAccountSize,
+} from "@solana/spl-account-compression";
 
 describe("memory-asset", () => {
   const provider = anchor.AnchorProvider.env();
@@ -15,6 +31,62 @@ describe("memory-asset", () => {
   let userAccountBump: number;
   let accessPolicyBump: number;
 
+  const BUBBLEGUM_PROGRAM_ID = new anchor.web3.PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+  const COMPRESSION_PROGRAM_ID = new anchor.web3.PublicKey("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+  const NOOP_PROGRAM_ID = new anchor.web3.PublicKey("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+  // Asset minted in "Mint Memory" and reused by later tests
+  const assetId = anchor.web3.Keypair.generate().publicKey;
+
+  const configPda = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  )[0];
+
+  const creditPda = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("credit"), owner.toBuffer()],
+    program.programId
+  )[0];
+
+  const deriveMemoryPda = (asset: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("memory"), asset.toBuffer()],
+      program.programId
+    )[0];
+
+  const deriveVersionPda = (asset: anchor.web3.PublicKey, version: number) => {
+    const versionBytes = Buffer.alloc(4);
+    versionBytes.writeUInt32LE(version);
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("memory_version"), asset.toBuffer(), versionBytes],
+      program.programId
+    )[0];
+  };
+
+  // Grant slots follow the zero-copy policy header (64 bytes), 152 bytes each
+  const fetchGrants = async (policy: anchor.web3.PublicKey) => {
+    const header = await program.account.accessPolicyAccount.fetch(policy);
+    const info = await provider.connection.getAccountInfo(policy);
+    return Array.from({ length: header.grantCount }, (_, i) => {
+      const slot = info.data.subarray(64 + i * 152, 64 + (i + 1) * 152);
+      return {
+        grantee: new anchor.web3.PublicKey(slot.subarray(0, 32)),
+        expiresAt: Number(slot.readBigInt64LE(32)),
+        maxAccess: slot.readUInt32LE(40),
+        currentAccess: slot.readUInt32LE(44),
+        permissions: slot[48],
+        scopeKind: slot[49],
+        effect: slot[50],
+        grantId: slot.readUInt32LE(88),
+        parentId: slot.readUInt32LE(92),
+        depth: slot[96],
+        notBefore: Number(slot.readBigInt64LE(104)),
+        quotaPeriod: slot.readUInt32LE(120),
+        quotaMax: slot.readUInt32LE(124),
+      };
+    });
+  };
+
   before(async () => {
     // Derive PDAs
     [userAccountPda, userAccountBump] = await anchor.web3.PublicKey.findProgramAddress(
@@ -26,6 +98,34 @@ describe("memory-asset", () => {
       [Buffer.from("access_policy"), owner.toBuffer()],
       program.programId
     );
+
+    // The config is shared by every test file, so only create it once
+    if (!(await provider.connection.getAccountInfo(configPda))) {
+      const programData = anchor.web3.PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+      )[0];
+
+      await program.methods
+        .initializeConfig({
+          admin: owner,
+          defaultStorageQuota: null,
+          treasury: owner,
+          baseStorageFee: new anchor.BN(0),
+          feePerKib: new anchor.BN(0),
+          complianceAuthority: null,
+          pruneBounty: new anchor.BN(0),
+          protocolFeeBps: 0,
+        })
+        .accounts({
+          config: configPda,
+          authority: owner,
+          program: program.programId,
+          programData,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
   });
 
   describe("Initialize User", () => {
@@ -35,6 +135,7 @@ describe("memory-asset", () => {
         .accounts({
           userAccount: userAccountPda,
           accessPolicy: accessPolicyPda,
+          creditAccount: creditPda,
           owner: owner,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
@@ -48,11 +149,14 @@ describe("memory-asset", () => {
       expect(userAccount.memoryCount.toNumber()).to.equal(0);
       expect(userAccount.totalStorageBytes.toNumber()).to.equal(0);
       expect(userAccount.accessPolicyVersion).to.equal(1);
+      expect(userAccount.schemaVersion).to.equal(1);
 
       // Fetch and verify access policy
       const accessPolicy = await program.account.accessPolicyAccount.fetch(accessPolicyPda);
       expect(accessPolicy.owner.toString()).to.equal(owner.toString());
-      expect(accessPolicy.grants).to.be.empty;
+      expect(accessPolicy.grantCount).to.equal(0);
+      expect(accessPolicy.capacity).to.equal(8);
+      expect(accessPolicy.schemaVersion).to.equal(2);
     });
 
     it("Should fail to initialize user account twice", async () => {
@@ -62,6 +166,7 @@ describe("memory-asset", () => {
           .accounts({
             userAccount: userAccountPda,
             accessPolicy: accessPolicyPda,
+            creditAccount: creditPda,
             owner: owner,
             systemProgram: anchor.web3.SystemProgram.programId,
           })
@@ -73,23 +178,132 @@ describe("memory-asset", () => {
     });
   });
 
+  describe("Schema Migration", () => {
+    it("Should reject migrating an account already on the current layout", async () => {
+      try {
+        await program.methods
+          .migrateUserAccount()
+          .accounts({
+            userAccount: userAccountPda,
+            owner: owner,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("AlreadyMigrated");
+      }
+    });
+
+    it("Should reject a legacy access policy before reading its bump", async () => {
+      // Loaded from tests/fixtures: a pre-schema_version policy next to a
+      // current user account, so only the policy layout can fail
+      const legacyOwner = new anchor.web3.PublicKey("G9jzPbEQy3nmRj42wCawaGCGxTouaUwGUamr2J2pRyWx");
+      const [legacyPolicyPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("access_policy"), legacyOwner.toBuffer()],
+        program.programId
+      );
+      const [legacyUserPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("user_account"), legacyOwner.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .revokeAccessGrants([])
+          .accounts({
+            accessPolicy: legacyPolicyPda,
+            userAccount: legacyUserPda,
+            owner: legacyOwner,
+            authority: owner,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("AccountNotMigrated");
+      }
+    });
+  });
+
+  describe("Prepaid Credit", () => {
+    it("Should deposit credit", async () => {
+      const amount = anchor.web3.LAMPORTS_PER_SOL / 10;
+
+      await program.methods
+        .depositCredit(new anchor.BN(amount))
+        .accounts({
+          creditAccount: creditPda,
+          owner: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const creditAccount = await program.account.creditAccount.fetch(creditPda);
+      expect(creditAccount.balance.toNumber()).to.equal(amount);
+      expect(creditAccount.totalDeposited.toNumber()).to.equal(amount);
+    });
+  });
+
   describe("Mint Memory", () => {
-    const arweaveId = "test-arweave-id-123456789012345678901234";
+    const arweaveId = "test-arweave-id-123456789012345678901234567";
     const contentHash = Array(32).fill(1);
     const metadataUri = "https://arweave.net/test-metadata";
+    const contentSize = 4096;
 
     it("Should mint memory successfully", async () => {
-      // Note: This is a simplified test without actual Bubblegum integration
-      // In production, you would need to set up Merkle tree accounts
+      // Note: Bubblegum CPI is not wired up yet, so throwaway keys stand in
+      // for the Merkle tree accounts
 
       const userAccountBefore = await program.account.userAccount.fetch(userAccountPda);
       const memoryCountBefore = userAccountBefore.memoryCount.toNumber();
 
-      // For testing purposes, we'll skip the actual minting
-      // and just verify the state updates would work
-      console.log("Memory count before:", memoryCountBefore);
-      console.log("Arweave ID:", arweaveId);
-      console.log("Content hash:", contentHash);
+      const tx = await program.methods
+        .mintMemory(
+          assetId,
+          { arweave: { txId: arweaveId } },
+          contentHash,
+          new anchor.BN(contentSize),
+          metadataUri,
+          null,
+          null,
+          null,
+          null
+        )
+        .accounts({
+          userAccount: userAccountPda,
+          config: configPda,
+          creditAccount: creditPda,
+          treasury: owner,
+          memoryAccount: deriveMemoryPda(assetId),
+          versionAccount: deriveVersionPda(assetId, 1),
+          owner: owner,
+          merkleTree: anchor.web3.Keypair.generate().publicKey,
+          treeAuthority: anchor.web3.Keypair.generate().publicKey,
+          bubblegumSigner: anchor.web3.Keypair.generate().publicKey,
+          agentAccount: null,
+          instructionsSysvar: null,
+          feeMint: null,
+          ownerTokenAccount: null,
+          treasuryTokenAccount: null,
+          tokenProgram: null,
+          logWrapper: NOOP_PROGRAM_ID,
+          compressionProgram: COMPRESSION_PROGRAM_ID,
+          bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      console.log("Mint memory transaction:", tx);
+
+      const memoryAccount = await program.account.memoryAccount.fetch(deriveMemoryPda(assetId));
+      expect(memoryAccount.owner.toString()).to.equal(owner.toString());
+      expect(memoryAccount.currentVersion).to.equal(1);
+
+      const userAccount = await program.account.userAccount.fetch(userAccountPda);
+      expect(userAccount.memoryCount.toNumber()).to.equal(memoryCountBefore + 1);
+      expect(userAccount.totalStorageBytes.toNumber()).to.equal(
+        userAccountBefore.totalStorageBytes.toNumber() + contentSize
+      );
     });
   });
 
@@ -103,32 +317,38 @@ describe("memory-asset", () => {
           expiresAt: null,
           maxAccess: null,
           currentAccess: 0,
+          scope: { all: {} },
+          effect: { allow: {} },
+          notBefore: null,
+          quota: null,
         },
       ];
 
       const tx = await program.methods
-        .updateAccessPolicy(grants, { deny: {} })
+        .updateAccessPolicy(grants, { deny: {} }, false)
         .accounts({
           accessPolicy: accessPolicyPda,
           userAccount: userAccountPda,
           owner: owner,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
         })
         .rpc();
 
       console.log("Update access policy transaction:", tx);
 
       // Verify policy was updated
-      const accessPolicy = await program.account.accessPolicyAccount.fetch(accessPolicyPda);
-      expect(accessPolicy.grants).to.have.lengthOf(1);
-      expect(accessPolicy.grants[0].grantee.toString()).to.equal(grantee.toString());
-      expect(accessPolicy.grants[0].permissions).to.equal(1);
+      const policyGrants = await fetchGrants(accessPolicyPda);
+      expect(policyGrants).to.have.lengthOf(1);
+      expect(policyGrants[0].grantee.toString()).to.equal(grantee.toString());
+      expect(policyGrants[0].permissions).to.equal(1);
 
       // Verify policy version was incremented
       const userAccount = await program.account.userAccount.fetch(userAccountPda);
       expect(userAccount.accessPolicyVersion).to.equal(2);
     });
 
-    it("Should fail with too many grants", async () => {
+    it("Should grow the policy past its initial capacity", async () => {
       const grants = Array(11)
         .fill(null)
         .map(() => ({
@@ -137,21 +357,358 @@ describe("memory-asset", () => {
           expiresAt: null,
           maxAccess: null,
           currentAccess: 0,
+          scope: { all: {} },
+          effect: { allow: {} },
+          notBefore: null,
+          quota: null,
         }));
 
+      await program.methods
+        .updateAccessPolicy(grants, { deny: {} }, false)
+        .accounts({
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const accessPolicy = await program.account.accessPolicyAccount.fetch(accessPolicyPda);
+      expect(accessPolicy.grantCount).to.equal(12);
+      expect(accessPolicy.capacity).to.equal(16);
+
+      // Slots stay sorted by grantee for binary search
+      const policyGrants = await fetchGrants(accessPolicyPda);
+      const sorted = [...policyGrants].sort((a, b) => a.grantee.toBuffer().compare(b.grantee.toBuffer()));
+      expect(policyGrants.map((g) => g.grantee.toString())).to.deep.equal(
+        sorted.map((g) => g.grantee.toString())
+      );
+    });
+
+    it("Should revoke access grants", async () => {
+      const [revoked] = await fetchGrants(accessPolicyPda);
+
+      await program.methods
+        .revokeAccessGrants([{ grantee: revoked.grantee, scope: null }])
+        .accounts({
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          authority: owner,
+        })
+        .rpc();
+
+      const policyGrants = await fetchGrants(accessPolicyPda);
+      expect(policyGrants).to.have.lengthOf(11);
+      expect(policyGrants.map((g) => g.grantee.toString())).to.not.include(revoked.grantee.toString());
+    });
+
+    it("Should add a grant scoped to a batch", async () => {
+      const grantee = anchor.web3.Keypair.generate().publicKey;
+      const grants = [
+        {
+          grantee: grantee,
+          permissions: 0b001,
+          expiresAt: null,
+          maxAccess: null,
+          currentAccess: 0,
+          scope: { batch: ["team-batch-1"] },
+          effect: { allow: {} },
+          notBefore: null,
+          quota: null,
+        },
+      ];
+
+      await program.methods
+        .updateAccessPolicy(grants, { deny: {} }, false)
+        .accounts({
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const grant = (await fetchGrants(accessPolicyPda)).find(
+        (g) => g.grantee.toString() === grantee.toString()
+      );
+      expect(grant.scopeKind).to.equal(2);
+    });
+
+    it("Should add a deny grant", async () => {
+      const grantee = anchor.web3.Keypair.generate().publicKey;
+      const grants = [
+        {
+          grantee: grantee,
+          permissions: 0b111,
+          expiresAt: null,
+          maxAccess: null,
+          currentAccess: 0,
+          scope: { all: {} },
+          effect: { deny: {} },
+          notBefore: null,
+          quota: null,
+        },
+      ];
+
+      await program.methods
+        .updateAccessPolicy(grants, { allow: {} }, false)
+        .accounts({
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const grant = (await fetchGrants(accessPolicyPda)).find(
+        (g) => g.grantee.toString() === grantee.toString()
+      );
+      expect(grant.effect).to.equal(1);
+
+      const accessPolicy = await program.account.accessPolicyAccount.fetch(accessPolicyPda);
+      expect(accessPolicy.defaultPolicy).to.equal(1);
+    });
+
+    it("Should add a scheduled grant with a daily quota", async () => {
+      const grantee = anchor.web3.Keypair.generate().publicKey;
+      const notBefore = Math.floor(Date.now() / 1000) + 3600;
+
+      await program.methods
+        .updateAccessPolicy(
+          [
+            {
+              grantee: grantee,
+              permissions: 0b001,
+              expiresAt: new anchor.BN(notBefore + 30 * 86400),
+              maxAccess: null,
+              currentAccess: 0,
+              scope: { all: {} },
+              effect: { allow: {} },
+              notBefore: new anchor.BN(notBefore),
+              quota: { maxAccess: 100, period: 86400 },
+            },
+          ],
+          { allow: {} },
+          false
+        )
+        .accounts({
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const grant = (await fetchGrants(accessPolicyPda)).find(
+        (g) => g.grantee.toString() === grantee.toString()
+      );
+      expect(grant.notBefore).to.equal(notBefore);
+      expect(grant.quotaPeriod).to.equal(86400);
+      expect(grant.quotaMax).to.equal(100);
+    });
+
+    it("Should reject a quota with an empty period", async () => {
       try {
         await program.methods
-          .updateAccessPolicy(grants, { deny: {} })
+          .updateAccessPolicy(
+            [
+              {
+                grantee: anchor.web3.Keypair.generate().publicKey,
+                permissions: 0b001,
+                expiresAt: null,
+                maxAccess: null,
+                currentAccess: 0,
+                scope: { all: {} },
+                effect: { allow: {} },
+                notBefore: null,
+                quota: { maxAccess: 10, period: 0 },
+              },
+            ],
+            { allow: {} },
+            false
+          )
           .accounts({
             accessPolicy: accessPolicyPda,
             userAccount: userAccountPda,
             owner: owner,
+            authority: owner,
+            systemProgram: anchor.web3.SystemProgram.programId,
           })
           .rpc();
         expect.fail("Should have thrown an error");
       } catch (error) {
-        expect(error).to.exist;
+        expect(error.toString()).to.include("InvalidAccessQuota");
+      }
+    });
+
+    it("Should refuse access once the period quota is used up", async () => {
+      const grantee = anchor.web3.Keypair.generate();
+
+      await program.methods
+        .updateAccessPolicy(
+          [
+            {
+              grantee: grantee.publicKey,
+              permissions: 0b011,
+              expiresAt: null,
+              maxAccess: null,
+              currentAccess: 0,
+              scope: { all: {} },
+              effect: { allow: {} },
+              notBefore: null,
+              quota: { maxAccess: 2, period: 86400 },
+            },
+          ],
+          { allow: {} },
+          false
+        )
+        .accounts({
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      // Distinct permissions keep the transactions from being deduplicated
+      const recordAccess = (permissions: number) =>
+        program.methods
+          .recordAccess(permissions)
+          .accounts({
+            accessPolicy: accessPolicyPda,
+            memoryAccount: deriveMemoryPda(assetId),
+            accessor: grantee.publicKey,
+          })
+          .signers([grantee])
+          .rpc();
+
+      await recordAccess(0b001);
+      await recordAccess(0b010);
+
+      const grant = (await fetchGrants(accessPolicyPda)).find((g) =>
+        g.grantee.equals(grantee.publicKey)
+      );
+      expect(grant.currentAccess).to.equal(2);
+
+      try {
+        await recordAccess(0b011);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("AccessGrantQuotaExceeded");
+      }
+    });
+
+    it("Should delegate a shared grant and revoke it transitively", async () => {
+      const delegator = anchor.web3.Keypair.generate();
+      const delegate = anchor.web3.Keypair.generate().publicKey;
+      const airdropSig = await provider.connection.requestAirdrop(
+        delegator.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdropSig);
+
+      await program.methods
+        .updateAccessPolicy(
+          [
+            {
+              grantee: delegator.publicKey,
+              permissions: 0b1001, // Read + Share
+              expiresAt: null,
+              maxAccess: 5,
+              currentAccess: 0,
+              scope: { all: {} },
+              effect: { allow: {} },
+              notBefore: null,
+              quota: null,
+            },
+          ],
+          { allow: {} },
+          false
+        )
+        .accounts({
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      // Write was never shared with the delegator
+      try {
+        await program.methods
+          .delegateAccess({
+            grantee: delegate,
+            permissions: 0b011,
+            expiresAt: null,
+            maxAccess: 3,
+            currentAccess: 0,
+            scope: { all: {} },
+            effect: { allow: {} },
+            notBefore: null,
+            quota: null,
+          })
+          .accounts({
+            accessPolicy: accessPolicyPda,
+            userAccount: userAccountPda,
+            owner: owner,
+            delegator: delegator.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([delegator])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidDelegation");
       }
+
+      await program.methods
+        .delegateAccess({
+          grantee: delegate,
+          permissions: 0b001,
+          expiresAt: null,
+          maxAccess: 3,
+          currentAccess: 0,
+          scope: { all: {} },
+          effect: { allow: {} },
+          notBefore: null,
+          quota: null,
+        })
+        .accounts({
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          delegator: delegator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([delegator])
+        .rpc();
+
+      const policyGrants = await fetchGrants(accessPolicyPda);
+      const parent = policyGrants.find((g) => g.grantee.equals(delegator.publicKey));
+      const child = policyGrants.find((g) => g.grantee.equals(delegate));
+      expect(child.parentId).to.equal(parent.grantId);
+      expect(child.depth).to.equal(1);
+
+      await program.methods
+        .revokeAccessGrants([{ grantee: delegator.publicKey, scope: null }])
+        .accounts({
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          authority: owner,
+        })
+        .rpc();
+
+      const remaining = (await fetchGrants(accessPolicyPda)).map((g) => g.grantee.toString());
+      expect(remaining).to.not.include(delegator.publicKey.toString());
+      expect(remaining).to.not.include(delegate.toString());
     });
 
     it("Should fail with invalid permissions", async () => {
@@ -163,16 +720,22 @@ describe("memory-asset", () => {
           expiresAt: null,
           maxAccess: null,
           currentAccess: 0,
+          scope: { all: {} },
+          effect: { allow: {} },
+          notBefore: null,
+          quota: null,
         },
       ];
 
       try {
         await program.methods
-          .updateAccessPolicy(grants, { deny: {} })
+          .updateAccessPolicy(grants, { deny: {} }, false)
           .accounts({
             accessPolicy: accessPolicyPda,
             userAccount: userAccountPda,
             owner: owner,
+            authority: owner,
+            systemProgram: anchor.web3.SystemProgram.programId,
           })
           .rpc();
         expect.fail("Should have thrown an error");
@@ -184,19 +747,97 @@ describe("memory-asset", () => {
 
   describe("Create Version", () => {
     it("Should create new version successfully", async () => {
-      const arweaveId = "test-arweave-id-v2-123456789012345678";
+      const arweaveId = "test-arweave-id-v2-123456789012345678901234";
       const contentHash = Array(32).fill(2);
 
       const tx = await program.methods
-        .createVersion(arweaveId, contentHash)
+        .createVersion({ arweave: { txId: arweaveId } }, contentHash, new anchor.BN(1024))
         .accounts({
           userAccount: userAccountPda,
+          config: configPda,
+          creditAccount: creditPda,
+          treasury: owner,
+          memoryAccount: deriveMemoryPda(assetId),
+          versionAccount: deriveVersionPda(assetId, 2),
           owner: owner,
+          feeMint: null,
+          ownerTokenAccount: null,
+          treasuryTokenAccount: null,
+          tokenProgram: null,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
         .rpc();
 
       console.log("Create version transaction:", tx);
+
+      const versionAccount = await program.account.memoryVersionAccount.fetch(
+        deriveVersionPda(assetId, 2)
+      );
+      expect(versionAccount.version).to.equal(2);
+      expect(versionAccount.locators).to.have.lengthOf(1);
+    });
+
+    it("Should create a version stored on IPFS", async () => {
+      const cid = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+      const contentHash = Array(32).fill(3);
+
+      await program.methods
+        .createVersion({ ipfs: { cid } }, contentHash, new anchor.BN(1024))
+        .accounts({
+          userAccount: userAccountPda,
+          config: configPda,
+          creditAccount: creditPda,
+          treasury: owner,
+          memoryAccount: deriveMemoryPda(assetId),
+          versionAccount: deriveVersionPda(assetId, 3),
+          owner: owner,
+          feeMint: null,
+          ownerTokenAccount: null,
+          treasuryTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("Should fail when the storage quota is exceeded", async () => {
+      const setQuota = (quota: anchor.BN | null) =>
+        program.methods
+          .setStorageQuota(quota)
+          .accounts({ config: configPda, userAccount: userAccountPda, admin: owner })
+          .rpc();
+
+      const userAccount = await program.account.userAccount.fetch(userAccountPda);
+      await setQuota(userAccount.totalStorageBytes);
+
+      try {
+        await program.methods
+          .createVersion(
+            { arweave: { txId: "quota-arweave-id-12345678901234567890123456" } },
+            Array(32).fill(4),
+            new anchor.BN(1)
+          )
+          .accounts({
+            userAccount: userAccountPda,
+            config: configPda,
+            creditAccount: creditPda,
+            treasury: owner,
+            memoryAccount: deriveMemoryPda(assetId),
+            versionAccount: deriveVersionPda(assetId, 4),
+            owner: owner,
+            feeMint: null,
+            ownerTokenAccount: null,
+            treasuryTokenAccount: null,
+            tokenProgram: null,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("StorageQuotaExceeded");
+      } finally {
+        await setQuota(null);
+      }
     });
 
     it("Should fail with invalid Arweave ID length", async () => {
@@ -205,10 +846,19 @@ describe("memory-asset", () => {
 
       try {
         await program.methods
-          .createVersion(arweaveId, contentHash)
+          .createVersion({ arweave: { txId: arweaveId } }, contentHash, new anchor.BN(1024))
           .accounts({
             userAccount: userAccountPda,
+            config: configPda,
+            creditAccount: creditPda,
+            treasury: owner,
+            memoryAccount: deriveMemoryPda(assetId),
+            versionAccount: deriveVersionPda(assetId, 4),
             owner: owner,
+            feeMint: null,
+            ownerTokenAccount: null,
+            treasuryTokenAccount: null,
+            tokenProgram: null,
             systemProgram: anchor.web3.SystemProgram.programId,
           })
           .rpc();
@@ -218,4 +868,1621 @@ describe("memory-asset", () => {
       }
     });
   });
+
+  describe("Storage Mirrors", () => {
+    it("Should add mirrors without bumping the version", async () => {
+      const mirrors = [
+        { ipfs: { cid: "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi" } },
+        { uri: { uri: "https://shdw-drive.genesysgo.net/example/memory-v1.json" } },
+      ];
+
+      await program.methods
+        .setStorageMirrors(mirrors)
+        .accounts({
+          memoryAccount: deriveMemoryPda(assetId),
+          versionAccount: deriveVersionPda(assetId, 1),
+          owner: owner,
+        })
+        .rpc();
+
+      const versionAccount = await program.account.memoryVersionAccount.fetch(
+        deriveVersionPda(assetId, 1)
+      );
+      expect(versionAccount.locators).to.have.lengthOf(3);
+
+      const memoryAccount = await program.account.memoryAccount.fetch(deriveMemoryPda(assetId));
+      expect(memoryAccount.currentVersion).to.equal(3);
+    });
+
+    it("Should migrate the primary locator when the content hash matches", async () => {
+      const newStorage = { arweave: { txId: "migrated-arweave-id-12345678901234567890123" } };
+
+      await program.methods
+        .migrateStorage(newStorage, Array(32).fill(1))
+        .accounts({
+          memoryAccount: deriveMemoryPda(assetId),
+          versionAccount: deriveVersionPda(assetId, 1),
+          owner: owner,
+        })
+        .rpc();
+
+      const versionAccount = await program.account.memoryVersionAccount.fetch(
+        deriveVersionPda(assetId, 1)
+      );
+      expect(versionAccount.locators[0].arweave.txId).to.equal(newStorage.arweave.txId);
+    });
+
+    it("Should reject migration with a different content hash", async () => {
+      try {
+        await program.methods
+          .migrateStorage(
+            { arweave: { txId: "another-arweave-id-123456789012345678901234" } },
+            Array(32).fill(9)
+          )
+          .accounts({
+            memoryAccount: deriveMemoryPda(assetId),
+            versionAccount: deriveVersionPda(assetId, 1),
+            owner: owner,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error).to.exist;
+      }
+    });
+  });
+
+  describe("Attest Memory", () => {
+    let attestationPda: anchor.web3.PublicKey;
+
+    before(async () => {
+      [attestationPda] = await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from("attestation"), assetId.toBuffer(), owner.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("Should attest to a memory successfully", async () => {
+      const tx = await program.methods
+        .attestMemory(assetId, { accuracy: {} }, 90, "https://example.com/review/1")
+        .accounts({
+          attestation: attestationPda,
+          memoryAccount: deriveMemoryPda(assetId),
+          attester: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      console.log("Attest memory transaction:", tx);
+
+      const attestation = await program.account.attestation.fetch(attestationPda);
+      expect(attestation.assetId.toString()).to.equal(assetId.toString());
+      expect(attestation.attester.toString()).to.equal(owner.toString());
+      expect(attestation.score).to.equal(90);
+    });
+
+    it("Should revoke an attestation", async () => {
+      await program.methods
+        .revokeAttestation()
+        .accounts({
+          attestation: attestationPda,
+          attester: owner,
+        })
+        .rpc();
+
+      const info = await provider.connection.getAccountInfo(attestationPda);
+      expect(info).to.be.null;
+    });
+
+    it("Should fail with out of range score", async () => {
+      try {
+        await program.methods
+          .attestMemory(assetId, { safety: {} }, 101, null)
+          .accounts({
+            attestation: attestationPda,
+            memoryAccount: deriveMemoryPda(assetId),
+            attester: owner,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error).to.exist;
+      }
+    });
+  });
+
+  describe("Register Agent", () => {
+    const agent = anchor.web3.Keypair.generate();
+    let agentPda: anchor.web3.PublicKey;
+
+    before(async () => {
+      [agentPda] = await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from("agent"), owner.toBuffer(), agent.publicKey.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("Should register an agent key", async () => {
+      await program.methods
+        .registerAgent()
+        .accounts({
+          agentAccount: agentPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          agent: agent.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([agent])
+        .rpc();
+
+      const agentAccount = await program.account.agentAccount.fetch(agentPda);
+      expect(agentAccount.owner.toString()).to.equal(owner.toString());
+      expect(agentAccount.agent.toString()).to.equal(agent.publicKey.toString());
+    });
+
+    // Mints a memory attributed to `agent`. The Ed25519 instruction covers
+    // `signedHash` and is signed by `signer`, so tests can make either disagree
+    // with what the mint claims.
+    const mintWithAgent = async ({
+      signer = agent,
+      signedHash = Array(32).fill(12),
+      signedAt,
+      withSignature = true,
+    }: {
+      signer?: anchor.web3.Keypair;
+      signedHash?: number[];
+      signedAt?: number;
+      withSignature?: boolean;
+    } = {}) => {
+      const agentAssetId = anchor.web3.Keypair.generate().publicKey;
+      const storage = { arweave: { txId: "agent-arweave-id-12345678901234567890123456" } };
+      if (signedAt === undefined) {
+        const slot = await provider.connection.getSlot();
+        signedAt = await provider.connection.getBlockTime(slot);
+      }
+
+      const signedAtBytes = Buffer.alloc(8);
+      signedAtBytes.writeBigInt64LE(BigInt(signedAt));
+      const message = Buffer.concat([
+        owner.toBuffer(),
+        agentAssetId.toBuffer(),
+        Buffer.from(signedHash),
+        program.coder.types.encode("StorageLocator", storage),
+        signedAtBytes,
+      ]);
+      const ed25519Ix = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: signer.secretKey,
+        message,
+      });
+
+      await program.methods
+        .mintMemory(
+          agentAssetId,
+          storage,
+          Array(32).fill(12),
+          new anchor.BN(1024),
+          "https://arweave.net/agent-metadata",
+          new anchor.BN(signedAt),
+          null,
+          null,
+          null
+        )
+        .accounts({
+          userAccount: userAccountPda,
+          config: configPda,
+          creditAccount: creditPda,
+          treasury: owner,
+          memoryAccount: deriveMemoryPda(agentAssetId),
+          versionAccount: deriveVersionPda(agentAssetId, 1),
+          owner: owner,
+          merkleTree: anchor.web3.Keypair.generate().publicKey,
+          treeAuthority: anchor.web3.Keypair.generate().publicKey,
+          bubblegumSigner: anchor.web3.Keypair.generate().publicKey,
+          agentAccount: agentPda,
+          instructionsSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          feeMint: null,
+          ownerTokenAccount: null,
+          treasuryTokenAccount: null,
+          tokenProgram: null,
+          logWrapper: NOOP_PROGRAM_ID,
+          compressionProgram: COMPRESSION_PROGRAM_ID,
+          bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .preInstructions(withSignature ? [ed25519Ix] : [])
+        .rpc();
+
+      return agentAssetId;
+    };
+
+    it("Should mint a memory signed by the agent", async () => {
+      const agentAssetId = await mintWithAgent();
+
+      const memoryAccount = await program.account.memoryAccount.fetch(
+        deriveMemoryPda(agentAssetId)
+      );
+      expect(memoryAccount.agent.toString()).to.equal(agent.publicKey.toString());
+    });
+
+    it("Should not mint with a signature from another key", async () => {
+      try {
+        await mintWithAgent({ signer: anchor.web3.Keypair.generate() });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidAgentSignature");
+      }
+    });
+
+    it("Should not mint when the agent signed different content", async () => {
+      try {
+        await mintWithAgent({ signedHash: Array(32).fill(13) });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidAgentSignature");
+      }
+    });
+
+    it("Should not mint with a stale agent signature", async () => {
+      const slot = await provider.connection.getSlot();
+      const now = await provider.connection.getBlockTime(slot);
+      try {
+        // MAX_AGENT_SIGNATURE_AGE is 300 seconds
+        await mintWithAgent({ signedAt: now - 310 });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("AgentSignatureExpired");
+      }
+    });
+
+    it("Should not mint without the Ed25519 instruction", async () => {
+      try {
+        await mintWithAgent({ withSignature: false });
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("MissingAgentSignature");
+      }
+    });
+
+    it("Should revoke an agent key", async () => {
+      await program.methods
+        .revokeAgent()
+        .accounts({
+          agentAccount: agentPda,
+          owner: owner,
+        })
+        .rpc();
+
+      const info = await provider.connection.getAccountInfo(agentPda);
+      expect(info).to.be.null;
+    });
+  });
+
+  describe("Freeze Memory", () => {
+    it("Should freeze a memory", async () => {
+      await program.methods
+        .freezeMemory()
+        .accounts({
+          memoryAccount: deriveMemoryPda(assetId),
+          config: configPda,
+          authority: owner,
+        })
+        .rpc();
+
+      const memoryAccount = await program.account.memoryAccount.fetch(deriveMemoryPda(assetId));
+      expect(memoryAccount.frozenBy.toString()).to.equal(owner.toString());
+    });
+
+    it("Should reject new versions while frozen", async () => {
+      const memoryAccount = await program.account.memoryAccount.fetch(deriveMemoryPda(assetId));
+
+      try {
+        await program.methods
+          .createVersion(
+            { arweave: { txId: "frozen-arweave-id-1234567890123456789012345" } },
+            Array(32).fill(7),
+            new anchor.BN(1024)
+          )
+          .accounts({
+            userAccount: userAccountPda,
+            config: configPda,
+            creditAccount: creditPda,
+            treasury: owner,
+            memoryAccount: deriveMemoryPda(assetId),
+            versionAccount: deriveVersionPda(assetId, memoryAccount.currentVersion + 1),
+            owner: owner,
+            feeMint: null,
+            ownerTokenAccount: null,
+            treasuryTokenAccount: null,
+            tokenProgram: null,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("MemoryFrozen");
+      }
+    });
+
+    it("Should thaw a memory", async () => {
+      await program.methods
+        .thawMemory()
+        .accounts({
+          memoryAccount: deriveMemoryPda(assetId),
+          config: configPda,
+          authority: owner,
+        })
+        .rpc();
+
+      const memoryAccount = await program.account.memoryAccount.fetch(deriveMemoryPda(assetId));
+      expect(memoryAccount.frozenBy).to.be.null;
+    });
+  });
+
+  describe("Expire Memory", () => {
+    it("Should reject expiring a memory without a deadline", async () => {
+      try {
+        await program.methods
+          .expireMemory()
+          .accounts({
+            memoryAccount: deriveMemoryPda(assetId),
+            cranker: owner,
+            merkleTree: anchor.web3.Keypair.generate().publicKey,
+            treeAuthority: anchor.web3.Keypair.generate().publicKey,
+            logWrapper: NOOP_PROGRAM_ID,
+            compressionProgram: COMPRESSION_PROGRAM_ID,
+            bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("MemoryNotExpired");
+      }
+    });
+  });
+
+  describe("Prune Grants", () => {
+    it("Should reject pruning a policy without dead grants", async () => {
+      try {
+        await program.methods
+          .pruneGrants()
+          .accounts({
+            accessPolicy: accessPolicyPda,
+            userAccount: userAccountPda,
+            config: configPda,
+            creditAccount: creditPda,
+            owner: owner,
+            cranker: owner,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("NothingToPrune");
+      }
+    });
+  });
+
+  describe("Redact Memory", () => {
+    const redactedAssetId = anchor.web3.Keypair.generate().publicKey;
+
+    before(async () => {
+      await program.methods
+        .mintMemory(
+          redactedAssetId,
+          { arweave: { txId: "redact-arweave-id-1234567890123456789012345" } },
+          Array(32).fill(9),
+          new anchor.BN(1024),
+          "https://arweave.net/redact-metadata",
+          null,
+          null,
+          null,
+          null
+        )
+        .accounts({
+          userAccount: userAccountPda,
+          config: configPda,
+          creditAccount: creditPda,
+          treasury: owner,
+          memoryAccount: deriveMemoryPda(redactedAssetId),
+          versionAccount: deriveVersionPda(redactedAssetId, 1),
+          owner: owner,
+          merkleTree: anchor.web3.Keypair.generate().publicKey,
+          treeAuthority: anchor.web3.Keypair.generate().publicKey,
+          bubblegumSigner: anchor.web3.Keypair.generate().publicKey,
+          agentAccount: null,
+          instructionsSysvar: null,
+          feeMint: null,
+          ownerTokenAccount: null,
+          treasuryTokenAccount: null,
+          tokenProgram: null,
+          logWrapper: NOOP_PROGRAM_ID,
+          compressionProgram: COMPRESSION_PROGRAM_ID,
+          bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("Should redact a memory and wipe its locators", async () => {
+      await program.methods
+        .redactMemory()
+        .accounts({
+          memoryAccount: deriveMemoryPda(redactedAssetId),
+          accessPolicy: accessPolicyPda,
+          config: configPda,
+          listing: null,
+          seller: null,
+          authority: owner,
+        })
+        .remainingAccounts([
+          {
+            pubkey: deriveVersionPda(redactedAssetId, 1),
+            isWritable: true,
+            isSigner: false,
+          },
+        ])
+        .rpc();
+
+      const memoryAccount = await program.account.memoryAccount.fetch(deriveMemoryPda(redactedAssetId));
+      expect(memoryAccount.status).to.deep.equal({ redacted: {} });
+
+      const versionAccount = await program.account.memoryVersionAccount.fetch(
+        deriveVersionPda(redactedAssetId, 1)
+      );
+      expect(versionAccount.locators).to.have.lengthOf(0);
+      expect(versionAccount.redactedAt).to.not.be.null;
+    });
+
+    it("Should not redact a memory twice", async () => {
+      try {
+        await program.methods
+          .redactMemory()
+          .accounts({
+            memoryAccount: deriveMemoryPda(redactedAssetId),
+            accessPolicy: accessPolicyPda,
+            config: configPda,
+            listing: null,
+            seller: null,
+            authority: owner,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("MemoryNotActive");
+      }
+    });
+
+    it("Should not record access to a redacted memory", async () => {
+      try {
+        await program.methods
+          .recordAccess(0b001)
+          .accounts({
+            accessPolicy: accessPolicyPda,
+            memoryAccount: deriveMemoryPda(redactedAssetId),
+            accessor: owner,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("MemoryNotActive");
+      }
+    });
+  });
+
+  describe("Access Requests", () => {
+    const requester = anchor.web3.Keypair.generate();
+    let requestPda: anchor.web3.PublicKey;
+
+    const requestAccess = () =>
+      program.methods
+        .requestAccess({ all: {} }, 0b001, Array(32).fill(7))
+        .accounts({
+          accessRequest: requestPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          requester: requester.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([requester])
+        .rpc();
+
+    before(async () => {
+      [requestPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("access_request"), owner.toBuffer(), requester.publicKey.toBuffer()],
+        program.programId
+      );
+      const airdropSig = await provider.connection.requestAirdrop(
+        requester.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdropSig);
+    });
+
+    it("Should deny an access request", async () => {
+      await requestAccess();
+      const request = await program.account.accessRequest.fetch(requestPda);
+      expect(request.requester.toString()).to.equal(requester.publicKey.toString());
+      expect(request.permissions).to.equal(1);
+
+      await program.methods
+        .denyRequest()
+        .accounts({
+          accessRequest: requestPda,
+          accessPolicy: accessPolicyPda,
+          owner: owner,
+          requester: requester.publicKey,
+          authority: owner,
+        })
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(requestPda)).to.be.null;
+      const grantees = (await fetchGrants(accessPolicyPda)).map((g) => g.grantee.toString());
+      expect(grantees).to.not.include(requester.publicKey.toString());
+    });
+
+    it("Should let the requester cancel and reclaim the rent", async () => {
+      await program.methods
+        .requestAccess({ all: {} }, 0b001, Array(32).fill(8))
+        .accounts({
+          accessRequest: requestPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          requester: requester.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([requester])
+        .rpc();
+      const balance = await provider.connection.getBalance(requester.publicKey);
+
+      await program.methods
+        .cancelRequest()
+        .accounts({
+          accessRequest: requestPda,
+          owner: owner,
+          requester: requester.publicKey,
+        })
+        .signers([requester])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(requestPda)).to.be.null;
+      expect(await provider.connection.getBalance(requester.publicKey)).to.be.greaterThan(balance);
+    });
+
+    it("Should approve an access request into a grant", async () => {
+      await requestAccess();
+
+      await program.methods
+        .approveRequest(null, 50)
+        .accounts({
+          accessRequest: requestPda,
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          requester: requester.publicKey,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(requestPda)).to.be.null;
+      const grant = (await fetchGrants(accessPolicyPda)).find((g) =>
+        g.grantee.equals(requester.publicKey)
+      );
+      expect(grant.permissions).to.equal(1);
+      expect(grant.maxAccess).to.equal(50);
+    });
+  });
+
+  describe("Paid Access", () => {
+    const buyer = anchor.web3.Keypair.generate();
+    const offerId = new anchor.BN(1);
+    let offerPda: anchor.web3.PublicKey;
+
+    before(async () => {
+      [offerPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("access_offer"), owner.toBuffer(), offerId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const airdropSig = await provider.connection.requestAirdrop(
+        buyer.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdropSig);
+    });
+
+    it("Should create an access offer", async () => {
+      await program.methods
+        .createAccessOffer(offerId, { all: {} }, 0b001, new anchor.BN(86400), new anchor.BN(1_000_000), null, null)
+        .accounts({
+          accessOffer: offerPda,
+          userAccount: userAccountPda,
+          acceptedMint: null,
+          owner: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const offer = await program.account.accessOffer.fetch(offerPda);
+      expect(offer.price.toNumber()).to.equal(1_000_000);
+      expect(offer.duration.toNumber()).to.equal(86400);
+    });
+
+    it("Should sell a time-limited grant", async () => {
+      const ownerBalance = await provider.connection.getBalance(owner);
+
+      await program.methods
+        .purchaseAccess()
+        .accounts({
+          accessOffer: offerPda,
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          config: configPda,
+          treasury: owner,
+          owner: owner,
+          buyer: buyer.publicKey,
+          acceptedMint: null,
+          buyerTokenAccount: null,
+          ownerTokenAccount: null,
+          treasuryTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+
+      // The treasury is the owner here, so the full price arrives
+      expect(await provider.connection.getBalance(owner)).to.be.greaterThan(ownerBalance);
+
+      const grant = (await fetchGrants(accessPolicyPda)).find((g) =>
+        g.grantee.equals(buyer.publicKey)
+      );
+      expect(grant.permissions).to.equal(1);
+      expect(grant.expiresAt).to.be.greaterThan(Date.now() / 1000);
+
+      const offer = await program.account.accessOffer.fetch(offerPda);
+      expect(offer.sales.toNumber()).to.equal(1);
+    });
+
+    it("Should refuse to sell past an owner-issued deny", async () => {
+      const deniedBuyer = anchor.web3.Keypair.generate();
+      const airdropSig = await provider.connection.requestAirdrop(
+        deniedBuyer.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdropSig);
+
+      await program.methods
+        .updateAccessPolicy(
+          [
+            {
+              grantee: deniedBuyer.publicKey,
+              permissions: 0b001,
+              expiresAt: null,
+              maxAccess: null,
+              currentAccess: 0,
+              scope: { all: {} },
+              effect: { deny: {} },
+              notBefore: null,
+              quota: null,
+            },
+          ],
+          { allow: {} },
+          false
+        )
+        .accounts({
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .purchaseAccess()
+          .accounts({
+            accessOffer: offerPda,
+            accessPolicy: accessPolicyPda,
+            userAccount: userAccountPda,
+            config: configPda,
+            treasury: owner,
+            owner: owner,
+            buyer: deniedBuyer.publicKey,
+            acceptedMint: null,
+            buyerTokenAccount: null,
+            ownerTokenAccount: null,
+            treasuryTokenAccount: null,
+            tokenProgram: null,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([deniedBuyer])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("AccessDenied");
+      }
+
+      const grant = (await fetchGrants(accessPolicyPda)).find((g) =>
+        g.grantee.equals(deniedBuyer.publicKey)
+      );
+      expect(grant.effect).to.equal(1);
+    });
+
+    it("Should close an access offer", async () => {
+      await program.methods
+        .closeAccessOffer()
+        .accounts({ accessOffer: offerPda, owner: owner })
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(offerPda)).to.be.null;
+    });
+  });
+
+  describe("Royalties", () => {
+    const buyer = anchor.web3.Keypair.generate();
+    const recipient = anchor.web3.Keypair.generate();
+    const offerId = new anchor.BN(3);
+    let offerPda: anchor.web3.PublicKey;
+
+    before(async () => {
+      [offerPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("access_offer"), owner.toBuffer(), offerId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const airdropSig = await provider.connection.requestAirdrop(
+        buyer.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdropSig);
+    });
+
+    it("Should reject royalties above the maximum", async () => {
+      try {
+        await program.methods
+          .setRoyalties([{ recipient: recipient.publicKey, bps: 6000 }])
+          .accounts({ memoryAccount: deriveMemoryPda(assetId), creator: owner })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidRoyalties");
+      }
+    });
+
+    it("Should set creator royalties", async () => {
+      await program.methods
+        .setRoyalties([{ recipient: recipient.publicKey, bps: 1000 }])
+        .accounts({ memoryAccount: deriveMemoryPda(assetId), creator: owner })
+        .rpc();
+
+      const memoryAccount = await program.account.memoryAccount.fetch(deriveMemoryPda(assetId));
+      expect(memoryAccount.creator.toString()).to.equal(owner.toString());
+      expect(memoryAccount.royalties[0].bps).to.equal(1000);
+    });
+
+    it("Should pay royalties on an asset-scoped access sale", async () => {
+      await program.methods
+        .createAccessOffer(
+          offerId,
+          { assets: [assetId] },
+          0b001,
+          new anchor.BN(3600),
+          new anchor.BN(500_000_000),
+          null,
+          null
+        )
+        .accounts({
+          accessOffer: offerPda,
+          userAccount: userAccountPda,
+          acceptedMint: null,
+          owner: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: deriveMemoryPda(assetId), isWritable: true, isSigner: false },
+        ])
+        .rpc();
+
+      const offer = await program.account.accessOffer.fetch(offerPda);
+      expect(offer.royalties[0].bps).to.equal(1000);
+
+      await program.methods
+        .purchaseAccess()
+        .accounts({
+          accessOffer: offerPda,
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          config: configPda,
+          treasury: owner,
+          owner: owner,
+          buyer: buyer.publicKey,
+          acceptedMint: null,
+          buyerTokenAccount: null,
+          ownerTokenAccount: null,
+          treasuryTokenAccount: null,
+          tokenProgram: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: deriveMemoryPda(assetId), isWritable: false, isSigner: false },
+          { pubkey: recipient.publicKey, isWritable: true, isSigner: false },
+        ])
+        .signers([buyer])
+        .rpc();
+
+      // 10% of the proceeds; the protocol fee is zero in these tests
+      expect(await provider.connection.getBalance(recipient.publicKey)).to.equal(50_000_000);
+    });
+
+    it("Should not sell access without paying the offer's royalties", async () => {
+      // Leaving out the covered memories and recipients used to skip royalties
+      try {
+        await program.methods
+          .purchaseAccess()
+          .accounts({
+            accessOffer: offerPda,
+            accessPolicy: accessPolicyPda,
+            userAccount: userAccountPda,
+            config: configPda,
+            treasury: owner,
+            owner: owner,
+            buyer: buyer.publicKey,
+            acceptedMint: null,
+            buyerTokenAccount: null,
+            ownerTokenAccount: null,
+            treasuryTokenAccount: null,
+            tokenProgram: null,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts([
+            { pubkey: deriveMemoryPda(assetId), isWritable: false, isSigner: false },
+          ])
+          .signers([buyer])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidRoyaltyRecipient");
+      }
+    });
+
+    it("Should lock royalties once an offer covers the memory", async () => {
+      try {
+        await program.methods
+          .setRoyalties([{ recipient: recipient.publicKey, bps: 100 }])
+          .accounts({ memoryAccount: deriveMemoryPda(assetId), creator: owner })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("RoyaltiesLocked");
+      }
+    });
+  });
+
+  describe("Token Payments", () => {
+    const buyer = anchor.web3.Keypair.generate();
+    const offerId = new anchor.BN(2);
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    let mint: anchor.web3.PublicKey;
+    let acceptedMintPda: anchor.web3.PublicKey;
+    let offerPda: anchor.web3.PublicKey;
+    let treasuryTokenAccount: anchor.web3.PublicKey;
+    let buyerTokenAccount: anchor.web3.PublicKey;
+
+    before(async () => {
+      const airdropSig = await provider.connection.requestAirdrop(
+        buyer.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdropSig);
+
+      // The treasury is the owner in these tests
+      mint = await createMint(provider.connection, payer, owner, null, 6);
+      treasuryTokenAccount = await createAccount(provider.connection, payer, mint, owner);
+      buyerTokenAccount = await createAccount(provider.connection, payer, mint, buyer.publicKey);
+      await mintTo(provider.connection, payer, mint, buyerTokenAccount, owner, 10_000_000);
+
+      [acceptedMintPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("accepted_mint"), mint.toBuffer()],
+        program.programId
+      );
+      [offerPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("access_offer"), owner.toBuffer(), offerId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+    });
+
+    it("Should accept a payment mint", async () => {
+      await program.methods
+        .addAcceptedMint(new anchor.BN(0), new anchor.BN(0))
+        .accounts({
+          config: configPda,
+          acceptedMint: acceptedMintPda,
+          mint,
+          treasuryTokenAccount,
+          admin: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const acceptedMint = await program.account.acceptedMint.fetch(acceptedMintPda);
+      expect(acceptedMint.treasuryTokenAccount.toString()).to.equal(treasuryTokenAccount.toString());
+    });
+
+    it("Should sell access for tokens", async () => {
+      await program.methods
+        .createAccessOffer(offerId, { all: {} }, 0b001, new anchor.BN(3600), new anchor.BN(2_500_000), mint, null)
+        .accounts({
+          accessOffer: offerPda,
+          userAccount: userAccountPda,
+          acceptedMint: acceptedMintPda,
+          owner: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .purchaseAccess()
+        .accounts({
+          accessOffer: offerPda,
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          config: configPda,
+          treasury: owner,
+          owner: owner,
+          buyer: buyer.publicKey,
+          acceptedMint: acceptedMintPda,
+          buyerTokenAccount,
+          ownerTokenAccount: treasuryTokenAccount,
+          treasuryTokenAccount,
+          tokenProgram: new anchor.web3.PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+
+      const buyerAccount = await getAccount(provider.connection, buyerTokenAccount);
+      expect(Number(buyerAccount.amount)).to.equal(7_500_000);
+
+      const grant = (await fetchGrants(accessPolicyPda)).find((g) =>
+        g.grantee.equals(buyer.publicKey)
+      );
+      expect(grant.permissions).to.equal(1);
+    });
+  });
+
+  describe("Marketplace", () => {
+    // A real compressed NFT, so listings can hold its leaf delegate
+    const merkleTree = anchor.web3.Keypair.generate();
+    const MAX_DEPTH = 14;
+    const treeAuthority = anchor.web3.PublicKey.findProgramAddressSync(
+      [merkleTree.publicKey.toBuffer()],
+      BUBBLEGUM_PROGRAM_ID
+    )[0];
+    const metadata: MetadataArgs = {
+      name: "Market memory",
+      symbol: "MEM",
+      uri: "https://arweave.net/market-metadata",
+      sellerFeeBasisPoints: 0,
+      primarySaleHappened: false,
+      isMutable: false,
+      editionNonce: null,
+      tokenStandard: TokenStandard.NonFungible,
+      collection: null,
+      uses: null,
+      tokenProgramVersion: TokenProgramVersion.Original,
+      creators: [],
+    };
+    let saleAssetId: anchor.web3.PublicKey;
+    const buyer = anchor.web3.Keypair.generate();
+    const stranger = anchor.web3.Keypair.generate();
+    const price = new anchor.BN(100_000_000);
+    let listingPda: anchor.web3.PublicKey;
+
+    const chainTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+    const inOneDay = async () => new anchor.BN((await chainTime()) + 86400);
+
+    const bubblegumAccounts = () => ({
+      merkleTree: merkleTree.publicKey,
+      treeAuthority,
+      logWrapper: NOOP_PROGRAM_ID,
+      compressionProgram: COMPRESSION_PROGRAM_ID,
+      bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    });
+
+    // The memory is the tree's only leaf, so its proof is all empty subtrees
+    const currentLeaf = async () => {
+      const tree = await ConcurrentMerkleTreeAccount.fromAccountAddress(
+        provider.connection,
+        merkleTree.publicKey
+      );
+      return {
+        root: Array.from(tree.getCurrentRoot()),
+        dataHash: Array.from(computeDataHash(metadata)),
+        creatorHash: Array.from(computeCreatorHash(metadata.creators)),
+        nonce: new anchor.BN(0),
+        index: 0,
+      };
+    };
+    const proof = Array.from({ length: MAX_DEPTH }, (_, level) => ({
+      pubkey: new anchor.web3.PublicKey(emptyNode(level)),
+      isWritable: false,
+      isSigner: false,
+    }));
+
+    const listFor = async (allowedBuyer: anchor.web3.PublicKey | null, expiresAt: anchor.BN) =>
+      program.methods
+        .listMemory(price, allowedBuyer, expiresAt, await currentLeaf())
+        .accounts({
+          listing: listingPda,
+          memoryAccount: deriveMemoryPda(saleAssetId),
+          seller: owner,
+          ...bubblegumAccounts(),
+        })
+        .remainingAccounts(proof)
+        .rpc();
+
+    const cancel = async () =>
+      program.methods
+        .cancelListing(await currentLeaf())
+        .accounts({
+          listing: listingPda,
+          memoryAccount: deriveMemoryPda(saleAssetId),
+          seller: owner,
+          ...bubblegumAccounts(),
+        })
+        .remainingAccounts(proof)
+        .rpc();
+
+    const buyAs = async (who: anchor.web3.Keypair) =>
+      program.methods
+        .buyMemory(price, await currentLeaf())
+        .accounts({
+          listing: listingPda,
+          memoryAccount: deriveMemoryPda(saleAssetId),
+          config: configPda,
+          treasury: owner,
+          seller: owner,
+          buyer: who.publicKey,
+          ...bubblegumAccounts(),
+        })
+        .remainingAccounts(proof)
+        .signers([who])
+        .rpc();
+
+    before(async () => {
+      for (const who of [buyer, stranger]) {
+        const airdropSig = await provider.connection.requestAirdrop(
+          who.publicKey,
+          anchor.web3.LAMPORTS_PER_SOL
+        );
+        await provider.connection.confirmTransaction(airdropSig);
+      }
+
+      const space = getConcurrentMerkleTreeAccountSize(MAX_DEPTH, 64);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.createAccount({
+            fromPubkey: owner,
+            newAccountPubkey: merkleTree.publicKey,
+            lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+            space,
+            programId: COMPRESSION_PROGRAM_ID,
+          }),
+          createCreateTreeInstruction(
+            {
+              treeAuthority,
+              merkleTree: merkleTree.publicKey,
+              payer: owner,
+              treeCreator: owner,
+              logWrapper: NOOP_PROGRAM_ID,
+              compressionProgram: COMPRESSION_PROGRAM_ID,
+            },
+            { maxDepth: MAX_DEPTH, maxBufferSize: 64, public: false }
+          )
+        ),
+        [merkleTree]
+      );
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          createMintV1Instruction(
+            {
+              treeAuthority,
+              leafOwner: owner,
+              leafDelegate: owner,
+              merkleTree: merkleTree.publicKey,
+              payer: owner,
+              treeDelegate: owner,
+              logWrapper: NOOP_PROGRAM_ID,
+              compressionProgram: COMPRESSION_PROGRAM_ID,
+            },
+            { message: metadata }
+          )
+        )
+      );
+      saleAssetId = await getLeafAssetId(merkleTree.publicKey, new anchor.BN(0));
+
+      [listingPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("listing"), saleAssetId.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .mintMemory(
+          saleAssetId,
+          { arweave: { txId: "market-arweave-id-1234567890123456789012345" } },
+          Array(32).fill(11),
+          new anchor.BN(1024),
+          "https://arweave.net/market-metadata",
+          null,
+          null,
+          null,
+          null
+        )
+        .accounts({
+          userAccount: userAccountPda,
+          config: configPda,
+          creditAccount: creditPda,
+          treasury: owner,
+          memoryAccount: deriveMemoryPda(saleAssetId),
+          versionAccount: deriveVersionPda(saleAssetId, 1),
+          owner: owner,
+          merkleTree: anchor.web3.Keypair.generate().publicKey,
+          treeAuthority: anchor.web3.Keypair.generate().publicKey,
+          bubblegumSigner: anchor.web3.Keypair.generate().publicKey,
+          agentAccount: null,
+          instructionsSysvar: null,
+          feeMint: null,
+          ownerTokenAccount: null,
+          treasuryTokenAccount: null,
+          tokenProgram: null,
+          logWrapper: NOOP_PROGRAM_ID,
+          compressionProgram: COMPRESSION_PROGRAM_ID,
+          bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("Should list a memory for a reserved buyer", async () => {
+      await listFor(buyer.publicKey, await inOneDay());
+
+      const memoryAccount = await program.account.memoryAccount.fetch(deriveMemoryPda(saleAssetId));
+      expect(memoryAccount.delegate.toString()).to.equal(listingPda.toString());
+    });
+
+    it("Should not change royalties while listed", async () => {
+      try {
+        await program.methods
+          .setRoyalties([{ recipient: stranger.publicKey, bps: 500 }])
+          .accounts({ memoryAccount: deriveMemoryPda(saleAssetId), creator: owner })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("MemoryListed");
+      }
+    });
+
+    it("Should reject a buyer the listing is not reserved for", async () => {
+      try {
+        await buyAs(stranger);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("BuyerNotAllowed");
+      }
+    });
+
+    it("Should cancel a listing", async () => {
+      await cancel();
+
+      expect(await provider.connection.getAccountInfo(listingPda)).to.be.null;
+      const memoryAccount = await program.account.memoryAccount.fetch(deriveMemoryPda(saleAssetId));
+      expect(memoryAccount.delegate).to.be.null;
+    });
+
+    it("Should reject a leaf from another asset", async () => {
+      try {
+        await program.methods
+          .listMemory(price, null, await inOneDay(), {
+            ...(await currentLeaf()),
+            nonce: new anchor.BN(1),
+          })
+          .accounts({
+            listing: listingPda,
+            memoryAccount: deriveMemoryPda(saleAssetId),
+            seller: owner,
+            ...bubblegumAccounts(),
+          })
+          .remainingAccounts(proof)
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidLeaf");
+      }
+    });
+
+    it("Should reject buying an expired listing", async () => {
+      const expiresAt = (await chainTime()) + 2;
+      await listFor(null, new anchor.BN(expiresAt));
+
+      while ((await chainTime()) <= expiresAt) {
+        await new Promise((resolve) => setTimeout(resolve, 500));
+      }
+
+      try {
+        await buyAs(buyer);
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("ListingExpired");
+      }
+
+      await cancel();
+    });
+
+    it("Should sell a listed memory with its compressed NFT", async () => {
+      await listFor(null, await inOneDay());
+      const rootBefore = (await currentLeaf()).root;
+
+      const buyerBalance = await provider.connection.getBalance(buyer.publicKey);
+      await buyAs(buyer);
+
+      const memoryAccount = await program.account.memoryAccount.fetch(deriveMemoryPda(saleAssetId));
+      expect(memoryAccount.owner.toString()).to.equal(buyer.publicKey.toString());
+      expect(memoryAccount.delegate).to.be.null;
+      expect(await provider.connection.getAccountInfo(listingPda)).to.be.null;
+      expect(await provider.connection.getBalance(buyer.publicKey)).to.be.lessThan(
+        buyerBalance - price.toNumber()
+      );
+      // The transfer rewrote the leaf
+      expect((await currentLeaf()).root).to.not.deep.equal(rootBefore);
+    });
+
+    it("Should lock royalties once the creator sells the memory", async () => {
+      try {
+        await program.methods
+          .setRoyalties([{ recipient: stranger.publicKey, bps: 500 }])
+          .accounts({ memoryAccount: deriveMemoryPda(saleAssetId), creator: owner })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("RoyaltiesLocked");
+      }
+    });
+  });
+
+  describe("Policy Templates", () => {
+    const templateName = "research-team";
+    const researcher = anchor.web3.Keypair.generate().publicKey;
+    const reviewer = anchor.web3.Keypair.generate().publicKey;
+    let templatePda: anchor.web3.PublicKey;
+    let bindingPda: anchor.web3.PublicKey;
+
+    const readGrant = (grantee: anchor.web3.PublicKey) => ({
+      grantee,
+      permissions: 0b001,
+      expiresAt: null,
+      maxAccess: null,
+      currentAccess: 0,
+      scope: { all: {} },
+      effect: { allow: {} },
+      notBefore: null,
+      quota: null,
+    });
+
+    before(async () => {
+      [templatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("policy_template"), owner.toBuffer(), Buffer.from(templateName)],
+        program.programId
+      );
+      [bindingPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("template_binding"),
+          owner.toBuffer(),
+          templatePda.toBuffer(),
+          anchor.web3.PublicKey.default.toBuffer(),
+        ],
+        program.programId
+      );
+    });
+
+    it("Should create a policy template", async () => {
+      await program.methods
+        .createPolicyTemplate(templateName, [readGrant(researcher)], { deny: {} })
+        .accounts({
+          template: templatePda,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const template = await program.account.policyTemplate.fetch(templatePda);
+      expect(template.name).to.equal(templateName);
+      expect(template.grants).to.have.lengthOf(1);
+      expect(template.version).to.equal(1);
+    });
+
+    it("Should apply a template to the owner's policy", async () => {
+      await program.methods
+        .applyPolicyTemplate(null, true, false)
+        .accounts({
+          template: templatePda,
+          binding: bindingPda,
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          memoryAccount: null,
+          owner: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const grantees = (await fetchGrants(accessPolicyPda)).map((g) => g.grantee.toString());
+      expect(grantees).to.include(researcher.toString());
+
+      const binding = await program.account.templateBinding.fetch(bindingPda);
+      expect(binding.appliedVersion).to.equal(1);
+      expect(binding.appliedKeys).to.have.lengthOf(1);
+    });
+
+    it("Should propagate template updates", async () => {
+      await program.methods
+        .updatePolicyTemplate([readGrant(reviewer)], { deny: {} })
+        .accounts({
+          template: templatePda,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .syncPolicyTemplate()
+        .accounts({
+          template: templatePda,
+          binding: bindingPda,
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          payer: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      // The researcher came from the old template version and is replaced
+      const grantees = (await fetchGrants(accessPolicyPda)).map((g) => g.grantee.toString());
+      expect(grantees).to.include(reviewer.toString());
+      expect(grantees).to.not.include(researcher.toString());
+
+      const binding = await program.account.templateBinding.fetch(bindingPda);
+      expect(binding.appliedVersion).to.equal(2);
+    });
+
+    it("Should not overwrite a grant the owner rewrote", async () => {
+      await program.methods
+        .updateAccessPolicy([{ ...readGrant(reviewer), effect: { deny: {} } }], { deny: {} }, false)
+        .accounts({
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .updatePolicyTemplate([readGrant(reviewer)], { deny: {} })
+        .accounts({
+          template: templatePda,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .syncPolicyTemplate()
+        .accounts({
+          template: templatePda,
+          binding: bindingPda,
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          payer: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      // The owner's deny stands and the binding no longer claims the slot
+      const grant = (await fetchGrants(accessPolicyPda)).find((g) => g.grantee.equals(reviewer));
+      expect(grant.effect).to.equal(1);
+
+      const binding = await program.account.templateBinding.fetch(bindingPda);
+      expect(binding.appliedKeys).to.have.lengthOf(0);
+    });
+
+    it("Should reject syncing an up-to-date template", async () => {
+      try {
+        await program.methods
+          .syncPolicyTemplate()
+          .accounts({
+            template: templatePda,
+            binding: bindingPda,
+            accessPolicy: accessPolicyPda,
+            userAccount: userAccountPda,
+            owner: owner,
+            payer: owner,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("TemplateUpToDate");
+      }
+    });
+
+    it("Should refund rent when a template shrinks", async () => {
+      const update = (grants) =>
+        program.methods
+          .updatePolicyTemplate(grants, { deny: {} })
+          .accounts({
+            template: templatePda,
+            authority: owner,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+
+      await update([readGrant(reviewer), readGrant(researcher)]);
+      const grownLamports = (await provider.connection.getAccountInfo(templatePda)).lamports;
+      await update([readGrant(reviewer)]);
+
+      // Only the rent-exempt minimum for the smaller account stays behind
+      const info = await provider.connection.getAccountInfo(templatePda);
+      const minimum = await provider.connection.getMinimumBalanceForRentExemption(info.data.length);
+      expect(info.lamports).to.equal(minimum);
+      expect(info.lamports).to.be.lessThan(grownLamports);
+    });
+  });
+
+  // Runs last: it wipes the shared policy
+  describe("Lockdown", () => {
+    it("Should revoke every grant and agent key at once", async () => {
+      const agent = anchor.web3.Keypair.generate();
+      const [agentPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("agent"), owner.toBuffer(), agent.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .registerAgent()
+        .accounts({
+          agentAccount: agentPda,
+          userAccount: userAccountPda,
+          owner: owner,
+          agent: agent.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([agent])
+        .rpc();
+
+      const versionBefore = (await program.account.userAccount.fetch(userAccountPda))
+        .accessPolicyVersion;
+
+      await program.methods
+        .lockdown()
+        .accounts({
+          accessPolicy: accessPolicyPda,
+          userAccount: userAccountPda,
+          owner: owner,
+        })
+        .remainingAccounts([{ pubkey: agentPda, isWritable: true, isSigner: false }])
+        .rpc();
+
+      const accessPolicy = await program.account.accessPolicyAccount.fetch(accessPolicyPda);
+      expect(accessPolicy.grantCount).to.equal(0);
+      expect(accessPolicy.defaultPolicy).to.equal(0);
+
+      const userAccount = await program.account.userAccount.fetch(userAccountPda);
+      expect(userAccount.accessPolicyVersion).to.equal(versionBefore + 1);
+
+      expect(await provider.connection.getAccountInfo(agentPda)).to.be.null;
+    });
+
+    it("Should not let a template sync refill a locked-down policy", async () => {
+      const [templatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("policy_template"), owner.toBuffer(), Buffer.from("research-team")],
+        program.programId
+      );
+      const [bindingPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("template_binding"),
+          owner.toBuffer(),
+          templatePda.toBuffer(),
+          anchor.web3.PublicKey.default.toBuffer(),
+        ],
+        program.programId
+      );
+
+      const template = await program.account.policyTemplate.fetch(templatePda);
+      await program.methods
+        .updatePolicyTemplate(template.grants, { allow: {} })
+        .accounts({
+          template: templatePda,
+          authority: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .syncPolicyTemplate()
+          .accounts({
+            template: templatePda,
+            binding: bindingPda,
+            accessPolicy: accessPolicyPda,
+            userAccount: userAccountPda,
+            owner: owner,
+            payer: owner,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("PolicyLockedDown");
+      }
+
+      const accessPolicy = await program.account.accessPolicyAccount.fetch(accessPolicyPda);
+      expect(accessPolicy.grantCount).to.equal(0);
+    });
+
+    it("Should not sell access into a locked-down policy", async () => {
+      const buyer = anchor.web3.Keypair.generate();
+      const offerId = new anchor.BN(4);
+      const [offerPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("access_offer"), owner.toBuffer(), offerId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const airdropSig = await provider.connection.requestAirdrop(
+        buyer.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(airdropSig);
+
+      await program.methods
+        .createAccessOffer(offerId, { all: {} }, 0b001, new anchor.BN(3600), new anchor.BN(1_000_000), null, null)
+        .accounts({
+          accessOffer: offerPda,
+          userAccount: userAccountPda,
+          acceptedMint: null,
+          owner: owner,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .purchaseAccess()
+          .accounts({
+            accessOffer: offerPda,
+            accessPolicy: accessPolicyPda,
+            userAccount: userAccountPda,
+            config: configPda,
+            treasury: owner,
+            owner: owner,
+            buyer: buyer.publicKey,
+            acceptedMint: null,
+            buyerTokenAccount: null,
+            ownerTokenAccount: null,
+            treasuryTokenAccount: null,
+            tokenProgram: null,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([buyer])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (error) {
+        expect(error.toString()).to.include("PolicyLockedDown");
+      }
+
+      const accessPolicy = await program.account.accessPolicyAccount.fetch(accessPolicyPda);
+      expect(accessPolicy.grantCount).to.equal(0);
+    });
+  });
 });
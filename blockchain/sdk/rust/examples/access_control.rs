@@ -1,5 +1,5 @@
 use memory_platform_sdk::{
-    MemoryPlatformClient, AccessGrant, Permission,
+    MemoryPlatformClient, AccessGrant, GrantEffect, GrantScope, Permission,
 };
 use anchor_client::Cluster;
 use solana_sdk::{pubkey::Pubkey, signature::Keypair};
@@ -53,6 +53,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         expires_at: Some(chrono::Utc::now().timestamp() + 86400), // 24 hours
         max_access: Some(10),
         current_access: 0,
+        scope: GrantScope::All,
+        effect: GrantEffect::Allow,
+        not_before: None,
+        quota: None,
     };
     
     match client.grant_access(asset_id, &grantee, grant).await {
@@ -140,7 +140,8 @@ impl MemoryPlatformClient {
             .json::<Vec<MemoryAsset>>()
             .await?;
 
-        Ok(response)
+        // Redacted memories are never served
+        Ok(response.into_iter().filter(|asset| !asset.redacted).collect())
     }
 
     /// Get a specific memory asset
@@ -154,7 +155,11 @@ impl MemoryPlatformClient {
             .await?;
 
         if response.status().is_success() {
-            Ok(response.json::<MemoryAsset>().await?)
+            let asset = response.json::<MemoryAsset>().await?;
+            if asset.redacted {
+                return Err(SdkError::ContentRedacted(asset_id.to_string()));
+            }
+            Ok(asset)
         } else {
             Err(SdkError::AssetNotFound(asset_id.to_string()))
         }
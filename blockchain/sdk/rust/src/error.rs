@@ -28,9 +28,24 @@ pub enum SdkError {
     #[error("Access denied")]
     AccessDenied,
 
+    #[error("Access grant expired")]
+    AccessGrantExpired,
+
+    #[error("Access grant exhausted")]
+    AccessGrantExhausted,
+
+    #[error("Access grant not valid yet")]
+    AccessGrantNotYetValid,
+
+    #[error("Access grant quota exceeded for the current period")]
+    AccessGrantQuotaExceeded,
+
     #[error("Asset not found: {0}")]
     AssetNotFound(String),
 
+    #[error("Content has been redacted: {0}")]
+    ContentRedacted(String),
+
     #[error("Invalid parameter: {0}")]
     InvalidParameter(String),
 
@@ -1,3 +1,4 @@
+use memory_asset::permissions::Permissions;
 use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 
@@ -48,6 +49,10 @@ pub struct MemoryAsset {
     pub updated_at: i64,
     pub metadata: serde_json::Value,
     pub batch_id: Option<String>,
+    #[serde(default)]
+    pub memory_type: Option<String>,
+    #[serde(default)]
+    pub redacted: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -70,13 +75,89 @@ pub struct AccessGrant {
     pub expires_at: Option<i64>,
     pub max_access: Option<u32>,
     pub current_access: u32,
+    #[serde(default)]
+    pub scope: GrantScope,
+    #[serde(default)]
+    pub effect: GrantEffect,
+    #[serde(default)]
+    pub not_before: Option<i64>,
+    #[serde(default)]
+    pub quota: Option<AccessQuota>,
 }
 
+/// Recurring access limit that refills every `period` seconds
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessQuota {
+    pub max_access: u32,
+    pub period: u32,
+    #[serde(default)]
+    pub period_start: i64,
+    #[serde(default)]
+    pub period_used: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GrantEffect {
+    #[default]
+    Allow,
+    Deny,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum GrantScope {
+    #[default]
+    All,
+    Assets(Vec<String>),
+    Batch(String),
+    MemoryType(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Permission {
     Read,
     Write,
     Transfer,
+    Share,
+    Delete,
+    Admin,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 6] = [
+        Permission::Read,
+        Permission::Write,
+        Permission::Transfer,
+        Permission::Share,
+        Permission::Delete,
+        Permission::Admin,
+    ];
+
+    /// On-chain flag for this permission
+    pub fn flag(self) -> Permissions {
+        match self {
+            Permission::Read => Permissions::READ,
+            Permission::Write => Permissions::WRITE,
+            Permission::Transfer => Permissions::TRANSFER,
+            Permission::Share => Permissions::SHARE,
+            Permission::Delete => Permissions::DELETE,
+            Permission::Admin => Permissions::ADMIN,
+        }
+    }
+
+    /// Expand an on-chain bitmap into its permissions
+    pub fn from_flags(flags: Permissions) -> Vec<Permission> {
+        Permission::ALL
+            .into_iter()
+            .filter(|permission| flags.contains(permission.flag()))
+            .collect()
+    }
+
+    /// Collapse a list of permissions into the on-chain bitmap
+    pub fn to_flags(permissions: &[Permission]) -> Permissions {
+        permissions
+            .iter()
+            .fold(Permissions::empty(), |flags, permission| flags | permission.flag())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
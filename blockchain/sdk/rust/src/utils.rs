@@ -3,6 +3,9 @@ use solana_sdk::{
     signature::{Keypair, Signature, Signer},
 };
 use crate::error::{SdkError, SdkResult};
+use crate::types::{
+    AccessGrant, AccessPolicy, DefaultPolicy, GrantEffect, GrantScope, MemoryAsset, Permission,
+};
 
 /// Derive a Program Derived Address (PDA)
 pub fn derive_pda(seeds: &[&[u8]], program_id: &Pubkey) -> SdkResult<(Pubkey, u8)> {
@@ -46,6 +49,83 @@ pub fn verify_signature(
     }
 }
 
+/// Check whether `requester` holds `permission` on `asset`, mirroring the
+/// program's `check_access` precedence:
+/// 1. The policy owner always has access.
+/// 2. A deny grant in effect covering the asset and permission denies access.
+/// 3. A valid allow grant covering the asset and permission allows access.
+/// 4. A covering allow grant that is expired, not yet valid, exhausted or over
+///    its period quota is reported as such.
+/// 5. Otherwise the default policy decides for `Read`; every other
+///    permission needs an explicit grant.
+pub fn check_access(
+    policy: &AccessPolicy,
+    asset: &MemoryAsset,
+    requester: &str,
+    permission: Permission,
+    now: i64,
+) -> SdkResult<()> {
+    if requester == policy.owner {
+        return Ok(());
+    }
+
+    let is_expired = |grant: &AccessGrant| grant.expires_at.map_or(false, |at| now > at);
+    let is_pending = |grant: &AccessGrant| grant.not_before.map_or(false, |at| now < at);
+    let covering: Vec<&AccessGrant> = policy
+        .grants
+        .iter()
+        .filter(|grant| {
+            grant.grantee == requester
+                && grant.permissions.contains(&permission)
+                && scope_matches(&grant.scope, asset)
+        })
+        .collect();
+
+    if covering
+        .iter()
+        .any(|grant| {
+            grant.effect == GrantEffect::Deny && !is_expired(grant) && !is_pending(grant)
+        })
+    {
+        return Err(SdkError::AccessDenied);
+    }
+
+    let mut invalid_grant = None;
+    for grant in covering.iter().filter(|grant| grant.effect == GrantEffect::Allow) {
+        if is_expired(grant) {
+            invalid_grant = Some(SdkError::AccessGrantExpired);
+        } else if is_pending(grant) {
+            invalid_grant = Some(SdkError::AccessGrantNotYetValid);
+        } else if grant.max_access.map_or(false, |max| grant.current_access >= max) {
+            invalid_grant = Some(SdkError::AccessGrantExhausted);
+        } else if grant.quota.as_ref().map_or(false, |quota| {
+            quota.period_used >= quota.max_access
+                && now < quota.period_start.saturating_add(quota.period as i64)
+        }) {
+            invalid_grant = Some(SdkError::AccessGrantQuotaExceeded);
+        } else {
+            return Ok(());
+        }
+    }
+    if let Some(error) = invalid_grant {
+        return Err(error);
+    }
+
+    match policy.default_policy {
+        DefaultPolicy::Allow if permission == Permission::Read => Ok(()),
+        _ => Err(SdkError::AccessDenied),
+    }
+}
+
+fn scope_matches(scope: &GrantScope, asset: &MemoryAsset) -> bool {
+    match scope {
+        GrantScope::All => true,
+        GrantScope::Assets(asset_ids) => asset_ids.contains(&asset.asset_id),
+        GrantScope::Batch(batch_id) => asset.batch_id.as_ref() == Some(batch_id),
+        GrantScope::MemoryType(memory_type) => asset.memory_type.as_ref() == Some(memory_type),
+    }
+}
+
 /// Convert lamports to SOL
 pub fn lamports_to_sol(lamports: u64) -> f64 {
     lamports as f64 / 1_000_000_000.0
@@ -59,6 +139,7 @@ pub fn sol_to_lamports(sol: f64) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::AccessQuota;
     use solana_sdk::signature::Keypair;
 
     #[test]
@@ -75,4 +156,118 @@ mod tests {
         
         assert!(verify_signature(&keypair.pubkey(), message, &signature).is_ok());
     }
+
+    fn test_asset() -> MemoryAsset {
+        MemoryAsset {
+            asset_id: "asset".to_string(),
+            owner: "owner".to_string(),
+            arweave_id: String::new(),
+            version: 1,
+            created_at: 0,
+            updated_at: 0,
+            metadata: serde_json::Value::Null,
+            batch_id: Some("batch".to_string()),
+            memory_type: None,
+            redacted: false,
+        }
+    }
+
+    fn test_grant(effect: GrantEffect, scope: GrantScope) -> AccessGrant {
+        AccessGrant {
+            grantee: "agent".to_string(),
+            permissions: vec![Permission::Read],
+            expires_at: None,
+            max_access: None,
+            current_access: 0,
+            scope,
+            effect,
+            not_before: None,
+            quota: None,
+        }
+    }
+
+    #[test]
+    fn test_deny_grant_overrides_allow_default() {
+        let mut policy = AccessPolicy {
+            owner: "owner".to_string(),
+            grants: vec![test_grant(GrantEffect::Deny, GrantScope::All)],
+            default_policy: DefaultPolicy::Allow,
+        };
+        let asset = test_asset();
+
+        assert!(matches!(
+            check_access(&policy, &asset, "agent", Permission::Read, 0),
+            Err(SdkError::AccessDenied)
+        ));
+        assert!(check_access(&policy, &asset, "other", Permission::Read, 0).is_ok());
+        assert!(check_access(&policy, &asset, "owner", Permission::Read, 0).is_ok());
+
+        // A deny grant wins over an allow grant for the same key
+        policy.grants.push(test_grant(GrantEffect::Allow, GrantScope::All));
+        assert!(check_access(&policy, &asset, "agent", Permission::Read, 0).is_err());
+
+        // Expired deny grants no longer apply
+        policy.grants[0].expires_at = Some(10);
+        assert!(check_access(&policy, &asset, "agent", Permission::Read, 20).is_ok());
+    }
+
+    #[test]
+    fn test_permission_flags_round_trip() {
+        let permissions = vec![Permission::Read, Permission::Share, Permission::Admin];
+        let flags = Permission::to_flags(&permissions);
+
+        assert_eq!(flags.bits(), 0b101001);
+        assert_eq!(Permission::from_flags(flags), permissions);
+        assert!(memory_asset::permissions::Permissions::validate(flags.bits()).is_ok());
+        assert!(memory_asset::permissions::Permissions::validate(0b1000_0000).is_err());
+    }
+
+    #[test]
+    fn test_scoped_allow_grant() {
+        let policy = AccessPolicy {
+            owner: "owner".to_string(),
+            grants: vec![test_grant(
+                GrantEffect::Allow,
+                GrantScope::Batch("batch".to_string()),
+            )],
+            default_policy: DefaultPolicy::Deny,
+        };
+        let mut asset = test_asset();
+
+        assert!(check_access(&policy, &asset, "agent", Permission::Read, 0).is_ok());
+        assert!(check_access(&policy, &asset, "agent", Permission::Write, 0).is_err());
+
+        asset.batch_id = Some("other".to_string());
+        assert!(check_access(&policy, &asset, "agent", Permission::Read, 0).is_err());
+    }
+
+    #[test]
+    fn test_not_before_and_period_quota() {
+        let mut grant = test_grant(GrantEffect::Allow, GrantScope::All);
+        grant.not_before = Some(100);
+        grant.quota = Some(AccessQuota {
+            max_access: 2,
+            period: 86400,
+            period_start: 0,
+            period_used: 2,
+        });
+        let policy = AccessPolicy {
+            owner: "owner".to_string(),
+            grants: vec![grant],
+            default_policy: DefaultPolicy::Deny,
+        };
+        let asset = test_asset();
+
+        assert!(matches!(
+            check_access(&policy, &asset, "agent", Permission::Read, 50),
+            Err(SdkError::AccessGrantNotYetValid)
+        ));
+        assert!(matches!(
+            check_access(&policy, &asset, "agent", Permission::Read, 200),
+            Err(SdkError::AccessGrantQuotaExceeded)
+        ));
+
+        // The quota refills once the period is over
+        assert!(check_access(&policy, &asset, "agent", Permission::Read, 86400).is_ok());
+    }
 }